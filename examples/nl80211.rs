@@ -23,7 +23,6 @@ use std::time::{Duration, Instant};
 use mio::unix::SourceFd;
 use mio::{Events, Interest, Poll, Token};
 
-use netlink_rust as netlink;
 use netlink_rust::generic;
 use netlink_rust::{Error, HardwareAddress, Message, MessageMode, Protocol, Socket};
 
@@ -163,167 +162,77 @@ impl fmt::Display for AccessPoint {
     }
 }
 
-fn parse_bss(data: &[u8]) -> Result<AccessPoint, Error> {
-    let mut bssid = None;
+fn access_point_from_bss(bss: &Bss) -> Result<AccessPoint, Error> {
     let mut ssid = None;
     let mut alpha2 = None;
-    let mut signal = None;
-    let mut frequency = None;
     let mut channel_1 = 0;
     let mut channel_2 = 0;
     let mut channel_width = 0;
-    let mut status = AccessPointStatus::None;
-    let (_, attrs) = netlink::Attribute::unpack_all(&data);
+    let status = match bss.status {
+        Some(BssStatus::Authenticated) => AccessPointStatus::Authenticated,
+        Some(BssStatus::Associated) => AccessPointStatus::Associated,
+        Some(BssStatus::IbssJoined) => AccessPointStatus::Joined,
+        None => AccessPointStatus::None,
+    };
     let mut ciphers = vec![];
     let mut akms = vec![];
     let mut pmf = information_element::ProtectedManagementFramesMode::Disabled;
     let mut csa = ChannelSwitchAnnouncement::None;
 
-    for attr in attrs {
-        let id = BssAttribute::from(attr.identifier);
-        match id {
-            BssAttribute::Frequency => {
-                frequency = attr.as_u32().ok();
+    let mut ies = bss.beacon_information_elements()?;
+    ies.append(&mut bss.information_elements()?);
+    for ref ie in ies {
+        match *ie {
+            InformationElement::Ssid(ref ie) => {
+                ssid = Some(ie.ssid.clone());
             }
-            BssAttribute::ChanWidth => (),
-            BssAttribute::SeenMsAgo => (),
-            BssAttribute::SignalMbm => {
-                signal = attr.as_i32().ok();
+            InformationElement::Country(ref ie) => {
+                alpha2 = Some(ie.alpha2.clone());
             }
-            BssAttribute::Status => match attr.as_u32() {
-                Ok(value) => {
-                    status = match value {
-                        0 => AccessPointStatus::Authenticated,
-                        1 => AccessPointStatus::Associated,
-                        2 => AccessPointStatus::Joined,
-                        _ => AccessPointStatus::None,
-                    }
+            InformationElement::ChannelSwitchAnnouncement(ref ie) => {
+                csa = ChannelSwitchAnnouncement::Announcement(ie.new_channel);
+            }
+            InformationElement::RobustSecurityNetwork(ref ie) => {
+                pmf = ie.pmf_mode();
+                for c in ie.ciphers.iter() {
+                    ciphers.push(c.clone());
+                }
+                for a in ie.akms.iter() {
+                    akms.push(a.clone());
                 }
-                Err(_) => (),
-            },
-            BssAttribute::Capability => (),
-            BssAttribute::Tsf => (),
-            BssAttribute::ParentTsf => (),
-            BssAttribute::BeaconInterval => (),
-            BssAttribute::BeaconTsf => (),
-            BssAttribute::Bssid => {
-                bssid = attr.as_hardware_address().ok();
             }
-            BssAttribute::ParentBssid => (),
-            /* Last seen receive in CLOCK_BOOTTIME */
-            BssAttribute::LastSeenBootTime => (),
-            BssAttribute::PrespData => (),
-            BssAttribute::BeaconIes => {
-                let attr_data = &attr.as_bytes();
-                let ies = InformationElement::parse_all(attr_data)?;
-                for ref ie in ies {
-                    match *ie {
-                        InformationElement::Ssid(ref ie) => {
-                            ssid = Some(ie.ssid.clone());
-                        }
-                        InformationElement::Country(ref ie) => {
-                            alpha2 = Some(ie.alpha2.clone());
-                        }
-                        InformationElement::ChannelSwitchAnnouncement(ref ie) => {
-                            csa = ChannelSwitchAnnouncement::Announcement(ie.new_channel);
-                        }
-                        InformationElement::RobustSecurityNetwork(ref ie) => {
-                            pmf = ie.pmf_mode();
-                            for c in ie.ciphers.iter() {
-                                ciphers.push(c.clone());
-                            }
-                            for a in ie.akms.iter() {
-                                akms.push(a.clone());
-                            }
-                        }
-                        InformationElement::ExtendedChannelSwitchAnnouncement(ref ie) => {
-                            csa = ChannelSwitchAnnouncement::Announcement(ie.new_channel);
-                        }
-                        InformationElement::HighThroughputOperation(ref ie) => {
-                            if channel_width < ie.width {
-                                channel_width = ie.width;
-                            }
-                            channel_1 = ie.primary_channel;
-                            channel_2 = ie.secondary_channel;
-                        }
-                        InformationElement::VeryHighThroughputOperation(ref ie) => {
-                            if channel_width < ie.width {
-                                channel_width = ie.width;
-                            }
-                            channel_1 = ie.channel;
-                            channel_2 = ie.secondary_channel;
-                        }
-                        InformationElement::Other(ref _ie) => {
-                            println!(
-                                "Information Element: {:?}, Len: {}",
-                                _ie.ie_id(),
-                                _ie.data.len()
-                            );
-                        }
-                    }
+            InformationElement::ExtendedChannelSwitchAnnouncement(ref ie) => {
+                csa = ChannelSwitchAnnouncement::Announcement(ie.new_channel);
+            }
+            InformationElement::HighThroughputOperation(ref ie) => {
+                if channel_width < ie.width {
+                    channel_width = ie.width;
                 }
+                channel_1 = ie.primary_channel;
+                channel_2 = ie.secondary_channel;
             }
-            BssAttribute::InformationElements => {
-                let attr_data = &attr.as_bytes();
-                let ies = InformationElement::parse_all(attr_data)?;
-                for ref ie in ies {
-                    match *ie {
-                        InformationElement::Ssid(ref ie) => {
-                            ssid = Some(ie.ssid.clone());
-                        }
-                        InformationElement::Country(ref ie) => {
-                            alpha2 = Some(ie.alpha2.clone());
-                        }
-                        InformationElement::ChannelSwitchAnnouncement(ref ie) => {
-                            csa = ChannelSwitchAnnouncement::Announcement(ie.new_channel);
-                        }
-                        InformationElement::RobustSecurityNetwork(ref ie) => {
-                            pmf = ie.pmf_mode();
-                            for c in ie.ciphers.iter() {
-                                ciphers.push(c.clone());
-                            }
-                            for a in ie.akms.iter() {
-                                akms.push(a.clone());
-                            }
-                        }
-                        InformationElement::ExtendedChannelSwitchAnnouncement(ref ie) => {
-                            csa = ChannelSwitchAnnouncement::Announcement(ie.new_channel);
-                        }
-                        InformationElement::HighThroughputOperation(ref ie) => {
-                            if channel_width < ie.width {
-                                channel_width = ie.width;
-                            }
-                            channel_1 = ie.primary_channel;
-                            channel_2 = ie.secondary_channel;
-                        }
-                        InformationElement::VeryHighThroughputOperation(ref ie) => {
-                            if channel_width < ie.width {
-                                channel_width = ie.width;
-                            }
-                            channel_1 = ie.channel;
-                            channel_2 = ie.secondary_channel;
-                        }
-                        InformationElement::Other(ref _ie) => {
-                            /*
-                            println!("Information Element: {:?}, Len: {}", _ie.ie_id(), _ie.data.len());
-                            */
-                        }
-                    }
+            InformationElement::VeryHighThroughputOperation(ref ie) => {
+                if channel_width < ie.width {
+                    channel_width = ie.width;
                 }
+                channel_1 = ie.channel;
+                channel_2 = ie.secondary_channel;
             }
-            _ => {
-                println!("Attribute: {:?}, Len: {}", id, attr.len());
+            InformationElement::Other(ref _ie) => {
+                /*
+                println!("Information Element: {:?}, Len: {}", _ie.ie_id(), _ie.data.len());
+                */
             }
         }
     }
-    if bssid.is_some() && ssid.is_some() && signal.is_some() && frequency.is_some() {
+    if ssid.is_some() && bss.signal_mbm.is_some() {
         let alpha2 = alpha2.unwrap_or(String::from("  "));
         return Ok(AccessPoint {
-            bssid: bssid.unwrap(),
+            bssid: bss.bssid,
             ssid: ssid.unwrap(),
             alpha2,
-            signal: signal.unwrap(),
-            frequency: frequency.unwrap(),
+            signal: bss.signal_mbm.unwrap(),
+            frequency: bss.frequency,
             channel_1,
             channel_2,
             channel_width,
@@ -340,20 +249,8 @@ fn parse_bss(data: &[u8]) -> Result<AccessPoint, Error> {
 fn parse_scan_result(message: &generic::Message) -> Result<AccessPoint, Error> {
     let command = nl80211::Command::from(message.command);
     if command == nl80211::Command::NewScanResults {
-        for ref attr in &message.attributes {
-            let attr_id = nl80211::Attribute::from(attr.identifier);
-            match attr_id {
-                nl80211::Attribute::Bss => {
-                    return parse_bss(&attr.as_bytes());
-                }
-                nl80211::Attribute::Generation => (),
-                nl80211::Attribute::Ifindex => (),
-                nl80211::Attribute::Wdev => (),
-                _ => {
-                    println!("Attribute: {:?}, Len: {}", attr_id, attr.len());
-                }
-            }
-        }
+        let bss = Bss::from_message(message)?;
+        return access_point_from_bss(&bss);
     }
     Err(io::Error::new(io::ErrorKind::NotFound, "Failed").into())
 }
@@ -375,27 +272,25 @@ fn scan_request_result(
     socket: &mut Socket,
     wireless_device: &WirelessInterface,
 ) -> Result<(), Error> {
-    println!("Get Scan for {}", wireless_device.interface_name);
-    {
-        let tx_msg =
-            wireless_device.prepare_message(nl80211::Command::GetScan, MessageMode::Dump)?;
-        socket.send_message(&tx_msg)?;
+    println!("Get Scan for {}", wireless_device);
+    let mut aps = vec![];
+    for bss in wireless_device.get_scan_results(socket)? {
+        aps.push(access_point_from_bss(&bss)?);
     }
+    print_scan_results(&mut aps)
+}
+
+fn scan_and_wait(socket: &mut Socket, wireless_device: &WirelessInterface) -> Result<(), Error> {
+    println!("Scan on {}", wireless_device);
+    let phy = nl80211::get_wireless_phys(socket, wireless_device.family.id)?
+        .into_iter()
+        .find(|phy| phy.identifier() == wireless_device.phy_id)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Phy not found"))?;
+    let request = nl80211::ScanRequest::new();
     let mut aps = vec![];
-    loop {
-        let messages = socket.receive_messages()?;
-        if messages.is_empty() {
-            break;
-        } else {
-            for m in messages {
-                if m.header.identifier == wireless_device.family.id {
-                    let (_, msg) = generic::Message::unpack(&m.data)?;
-                    aps.push(parse_scan_result(&msg)?);
-                } else {
-                    println!("Unknown message {}", m.header);
-                }
-            }
-        }
+    let timeout = Some(Duration::from_secs(30));
+    for bss in wireless_device.scan_and_wait(socket, &phy, &request, timeout)? {
+        aps.push(access_point_from_bss(&bss)?);
     }
     print_scan_results(&mut aps)
 }
@@ -560,25 +455,11 @@ impl Monitor {
                 let change = nl80211::RegulatoryChange::from_message(&message)?;
                 println!("[{}] Regulatory Change {}", device_id, change);
             }
-            nl80211::Command::Connect => {
-                for ref attr in &message.attributes {
-                    let id = nl80211::Attribute::from(attr.identifier);
-                    match id {
-                        nl80211::Attribute::StatusCode => {
-                            let status = attr.as_u16()?;
-                            if status == 0 {
-                                println!("[{}] Connect status: OK", device_id);
-                            } else {
-                                println!("[{}] Connect status: {}", device_id, status);
-                            }
-                        }
-                        _ => (),
-                    }
+            nl80211::Command::Connect | nl80211::Command::Roam | nl80211::Command::Disconnect => {
+                if let Some(event) = nl80211::ConnectionEvent::from_message(&message) {
+                    println!("[{}] {}", device_id, event);
                 }
             }
-            nl80211::Command::Disconnect => {
-                println!("[{}] Disconnect", device_id);
-            }
             _ => {
                 println!("[{}] Event Command: {:?}", device_id, command);
                 for ref attr in &message.attributes {
@@ -778,8 +659,12 @@ fn main() {
     let device = if user_command.requires_device() {
         let mut devices = nl80211::get_wireless_interfaces(&mut control_socket, &family)
             .expect("Failed to get nl80211 wireless interfaces");
+        // Only devices with a network interface can be used by the commands
+        devices.retain(|d| d.interface_index.is_some());
         if let Some(if_name) = opt.interface {
-            devices.into_iter().find(|d| d.interface_name == if_name)
+            devices
+                .into_iter()
+                .find(|d| d.interface_name.as_ref() == Some(&if_name))
         } else {
             if devices.is_empty() {
                 None
@@ -796,14 +681,14 @@ fn main() {
     }
     if user_command.requires_device() {
         if let Some(dev) = device {
-            println!("Using interface {}", dev.interface_name);
+            println!("Using interface {}", dev);
             match user_command {
                 UserCommand::Monitor => {
                     let mut monitor = Monitor::new(uid == 0, dev).unwrap();
                     monitor.run().unwrap();
                 }
                 UserCommand::Scan => {
-                    dev.trigger_scan(&mut control_socket).unwrap();
+                    scan_and_wait(&mut control_socket, &dev).unwrap();
                 }
                 UserCommand::ScanResults => {
                     scan_request_result(&mut control_socket, &dev).unwrap();
@@ -820,13 +705,18 @@ fn main() {
                         .expect("Failed to set regulatory domain");
                 }
                 UserCommand::GetSurvey => {
-                    dev.get_survey(&mut control_socket).unwrap();
+                    for survey in dev.get_survey(&mut control_socket).unwrap() {
+                        println!("{}", survey);
+                    }
                 }
                 UserCommand::SetChannel { channel } => {
-                    dev.set_channel(&mut control_socket, channel).unwrap();
+                    let chandef = nl80211::Chandef::new(channel);
+                    dev.set_channel(&mut control_socket, &chandef).unwrap();
                 }
                 UserCommand::GetStation => {
-                    dev.get_station(&mut control_socket).unwrap();
+                    for station in dev.get_station(&mut control_socket).unwrap() {
+                        println!("{}", station);
+                    }
                 }
                 _ => (),
             }
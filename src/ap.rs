@@ -0,0 +1,612 @@
+//! ## Access point
+//!
+//! Beacon and BSS configuration for starting an access point.
+
+use std::io;
+
+use byteorder::{ByteOrder, NativeEndian};
+
+use crate::attributes::{Attribute, AuthenticationType, HiddenSsid};
+use crate::chandef::{channel_number, Chandef};
+use crate::connect::{ControlPortFlags, CryptoSettings, WpaVersions};
+use crate::information_element::{AuthenticationKeyManagement, CipherSuite};
+use crate::information_element_ids::InformationElementId;
+use crate::pack::{flag_attribute, nested_attribute};
+use crate::wireless_phy::{FeatureFlags, InterfaceTypeFlags, WirelessPhy};
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{Error, HardwareAddress, NativePack};
+
+const MAX_SSID_LENGTH: usize = 32;
+const MAX_SUPPORTED_RATES: usize = 8;
+const MAX_BASIC_RATES: usize = 32;
+const MAX_BEACON_INTERVAL: u16 = 10000;
+const MAX_FRAME_LENGTH: usize = 2304;
+/// Header and fixed fields of a probe response, the elements follow
+const PROBE_RESPONSE_ELEMENTS_OFFSET: usize = 36;
+
+/// Capability information, ESS
+const CAPABILITY_ESS: u16 = 1 << 0;
+/// Capability information, privacy
+const CAPABILITY_PRIVACY: u16 = 1 << 4;
+
+/// Append an information element to a frame
+fn push_element(frame: &mut Vec<u8>, identifier: InformationElementId, data: &[u8]) {
+    frame.push(u8::from(identifier));
+    frame.push(data.len() as u8);
+    frame.extend_from_slice(data);
+}
+
+/// Beacon frames, either given as is or built from the SSID and information elements
+#[derive(Clone)]
+enum Beacon {
+    Frames { head: Vec<u8>, tail: Vec<u8> },
+    Elements { rates: Vec<u8>, tail: Vec<u8> },
+}
+
+/// Access point configuration
+///
+/// The beacon is built from the SSID, the rates and the information elements unless the head
+/// and tail frames are given with `frames`. Privacy is requested when WPA versions or ciphers
+/// are given.
+#[derive(Clone)]
+pub struct BeaconConfig {
+    ssid: Vec<u8>,
+    beacon: Beacon,
+    beacon_interval: u16,
+    dtim_period: u8,
+    hidden_ssid: HiddenSsid,
+    crypto: CryptoSettings,
+    privacy: bool,
+    auth_type: Option<AuthenticationType>,
+    chandef: Option<Chandef>,
+    inactivity_timeout: Option<u16>,
+    probe_response: Option<Vec<u8>>,
+}
+
+impl BeaconConfig {
+    /// Access point for the SSID, beaconing every 100 TU with a DTIM period of 2
+    pub fn new(ssid: &[u8]) -> BeaconConfig {
+        BeaconConfig {
+            ssid: ssid.to_vec(),
+            beacon: Beacon::Elements {
+                rates: vec![],
+                tail: vec![],
+            },
+            beacon_interval: 100,
+            dtim_period: 2,
+            hidden_ssid: HiddenSsid::NotInUse,
+            crypto: CryptoSettings::default(),
+            privacy: false,
+            auth_type: None,
+            chandef: None,
+            inactivity_timeout: None,
+            probe_response: None,
+        }
+    }
+
+    /// Use prebuilt beacon frames, the part before and after the TIM element
+    pub fn frames(mut self, head: &[u8], tail: &[u8]) -> BeaconConfig {
+        self.beacon = Beacon::Frames {
+            head: head.to_vec(),
+            tail: tail.to_vec(),
+        };
+        self
+    }
+
+    /// Supported rates in units of 500 kbit/s, basic rates have the most significant bit set
+    pub fn rates(mut self, supported_rates: &[u8]) -> BeaconConfig {
+        if let Beacon::Elements { ref mut rates, .. } = self.beacon {
+            *rates = supported_rates.to_vec();
+        }
+        self
+    }
+
+    /// Information elements to add to the beacon after the TIM element
+    pub fn information_elements(mut self, data: &[u8]) -> BeaconConfig {
+        if let Beacon::Elements { ref mut tail, .. } = self.beacon {
+            *tail = data.to_vec();
+        }
+        self
+    }
+
+    /// Beacon interval in TU
+    pub fn beacon_interval(mut self, interval: u16) -> BeaconConfig {
+        self.beacon_interval = interval;
+        self
+    }
+
+    /// Number of beacon intervals between DTIMs
+    pub fn dtim_period(mut self, period: u8) -> BeaconConfig {
+        self.dtim_period = period;
+        self
+    }
+
+    pub fn hidden_ssid(mut self, hidden: HiddenSsid) -> BeaconConfig {
+        self.hidden_ssid = hidden;
+        self
+    }
+
+    pub fn wpa_versions(mut self, versions: WpaVersions) -> BeaconConfig {
+        self.crypto.wpa_versions = Some(versions);
+        self
+    }
+
+    /// Add a pairwise cipher suite
+    pub fn pairwise_cipher(mut self, cipher: CipherSuite) -> BeaconConfig {
+        self.crypto.pairwise_ciphers.push(cipher);
+        self
+    }
+
+    pub fn group_cipher(mut self, cipher: CipherSuite) -> BeaconConfig {
+        self.crypto.group_cipher = Some(cipher);
+        self
+    }
+
+    /// Add an authentication and key management suite
+    pub fn akm_suite(mut self, akm: AuthenticationKeyManagement) -> BeaconConfig {
+        self.crypto.akm_suites.push(akm);
+        self
+    }
+
+    /// Block data traffic of stations until their control port is authorized
+    ///
+    /// `ethertype` defaults to EAPOL (0x888e).
+    pub fn control_port(mut self, ethertype: Option<u16>, flags: ControlPortFlags) -> BeaconConfig {
+        self.crypto.control_port = Some((ethertype, flags));
+        self
+    }
+
+    /// Request privacy without WPA, for WEP
+    pub fn privacy(mut self) -> BeaconConfig {
+        self.privacy = true;
+        self
+    }
+
+    pub fn auth_type(mut self, auth_type: AuthenticationType) -> BeaconConfig {
+        self.auth_type = Some(auth_type);
+        self
+    }
+
+    /// Operating channel, the driver selects one if not given
+    pub fn chandef(mut self, chandef: Chandef) -> BeaconConfig {
+        self.chandef = Some(chandef);
+        self
+    }
+
+    /// Disconnect stations inactive for the given number of seconds
+    pub fn inactivity_timeout(mut self, timeout: u16) -> BeaconConfig {
+        self.inactivity_timeout = Some(timeout);
+        self
+    }
+
+    /// Probe response template for drivers answering probe requests in firmware
+    pub fn probe_response(mut self, frame: &[u8]) -> BeaconConfig {
+        self.probe_response = Some(frame.to_vec());
+        self
+    }
+
+    fn has_privacy(&self) -> bool {
+        self.privacy || self.crypto.has_privacy()
+    }
+
+    /// Build the beacon head, the frame header, fixed fields and elements before the TIM
+    fn beacon_head(&self, address: HardwareAddress, rates: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0x80, 0x00, 0x00, 0x00];
+        frame.extend_from_slice(&[0xff; 6]);
+        let mut buffer = [0u8; 6];
+        address.pack_unchecked(&mut buffer);
+        frame.extend_from_slice(&buffer);
+        frame.extend_from_slice(&buffer);
+        // Sequence control and timestamp are filled in by the driver
+        frame.extend_from_slice(&[0u8; 10]);
+        frame.extend_from_slice(&self.beacon_interval.to_le_bytes());
+        let mut capabilities = CAPABILITY_ESS;
+        if self.has_privacy() {
+            capabilities |= CAPABILITY_PRIVACY;
+        }
+        frame.extend_from_slice(&capabilities.to_le_bytes());
+        match self.hidden_ssid {
+            HiddenSsid::NotInUse => {
+                push_element(&mut frame, InformationElementId::Ssid, &self.ssid)
+            }
+            HiddenSsid::ZeroLength => push_element(&mut frame, InformationElementId::Ssid, &[]),
+            HiddenSsid::ZeroContents => push_element(
+                &mut frame,
+                InformationElementId::Ssid,
+                &vec![0u8; self.ssid.len()],
+            ),
+        }
+        let count = rates.len().min(MAX_SUPPORTED_RATES);
+        push_element(
+            &mut frame,
+            InformationElementId::SupportRates,
+            &rates[..count],
+        );
+        if let Some(ref chandef) = self.chandef {
+            if let Some(channel) = ds_channel(chandef.control_freq) {
+                push_element(
+                    &mut frame,
+                    InformationElementId::DirectSpectrumParameters,
+                    &[channel],
+                );
+            }
+        }
+        frame
+    }
+
+    /// Check the configuration against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if !phy.interface_types().contains(InterfaceTypeFlags::AP) {
+            return invalid("Access point not supported");
+        }
+        if self.ssid.is_empty() || self.ssid.len() > MAX_SSID_LENGTH {
+            return invalid("Invalid SSID");
+        }
+        match self.beacon {
+            Beacon::Frames { ref head, ref tail } => {
+                if head.is_empty() || head.len() + tail.len() > MAX_FRAME_LENGTH {
+                    return invalid("Invalid beacon frames");
+                }
+            }
+            Beacon::Elements {
+                ref rates,
+                ref tail,
+            } => {
+                if rates.is_empty() {
+                    return invalid("No supported rates");
+                }
+                if tail.len() > MAX_FRAME_LENGTH {
+                    return invalid("Information elements too long");
+                }
+            }
+        }
+        if self.beacon_interval == 0 || self.beacon_interval > MAX_BEACON_INTERVAL {
+            return invalid("Invalid beacon interval");
+        }
+        if self.dtim_period == 0 {
+            return invalid("Invalid DTIM period");
+        }
+        if let Some(ref chandef) = self.chandef {
+            chandef.validate()?;
+        }
+        if self.inactivity_timeout.is_some()
+            && !phy.features().contains(FeatureFlags::INACTIVITY_TIMER)
+        {
+            return invalid("Inactivity timer not supported");
+        }
+        if let Some(ref frame) = self.probe_response {
+            if frame.len() > MAX_FRAME_LENGTH {
+                return invalid("Probe response too long");
+            }
+        }
+        self.crypto.validate(phy)
+    }
+
+    /// Beacon head and tail, `address` is the address of the access point interface
+    fn beacon_frames(&self, address: HardwareAddress) -> (Vec<u8>, Vec<u8>) {
+        match self.beacon {
+            Beacon::Frames { ref head, ref tail } => (head.clone(), tail.clone()),
+            Beacon::Elements {
+                ref rates,
+                ref tail,
+            } => {
+                let mut extended = vec![];
+                if rates.len() > MAX_SUPPORTED_RATES {
+                    push_element(
+                        &mut extended,
+                        InformationElementId::ExtededSupportRates,
+                        &rates[MAX_SUPPORTED_RATES..],
+                    );
+                }
+                extended.extend_from_slice(tail);
+                (self.beacon_head(address, rates), extended)
+            }
+        }
+    }
+
+    /// Append the beacon frames to a start AP or set beacon message
+    ///
+    /// `address` is the address of the access point interface, used for building the beacon.
+    pub fn append_beacon_attributes(
+        &self,
+        message: &mut generic::Message,
+        address: HardwareAddress,
+    ) {
+        let (head, tail) = self.beacon_frames(address);
+        message.append_attribute(netlink::Attribute::new_bytes(Attribute::BeaconHead, &head));
+        message.append_attribute(netlink::Attribute::new_bytes(Attribute::BeaconTail, &tail));
+        if let Some(ref frame) = self.probe_response {
+            message.append_attribute(netlink::Attribute::new_bytes(Attribute::ProbeResp, frame));
+        }
+    }
+
+    /// Append the beacon used during a channel switch to a channel switch message
+    ///
+    /// A beacon built from elements gets a channel switch announcement element added, prebuilt
+    /// frames must contain one. The offsets of the switch counters in the beacon and probe
+    /// response are nested along with the frames.
+    pub fn append_channel_switch_attributes(
+        &self,
+        message: &mut generic::Message,
+        address: HardwareAddress,
+        chandef: &Chandef,
+        count: u8,
+        block_tx: bool,
+    ) {
+        let (head, mut tail) = self.beacon_frames(address);
+        if let Beacon::Elements { .. } = self.beacon {
+            let channel = channel_number(chandef.control_freq).unwrap_or(0);
+            let mut announcement = vec![];
+            push_element(
+                &mut announcement,
+                InformationElementId::ChannelSwitchAnnouncement,
+                &[block_tx as u8, channel, count],
+            );
+            announcement.extend_from_slice(&tail);
+            tail = announcement;
+        }
+        let mut attrs = vec![
+            netlink::Attribute::new_bytes(Attribute::BeaconHead, &head),
+            netlink::Attribute::new_bytes(Attribute::BeaconTail, &tail),
+        ];
+        let offsets = counter_offsets(&tail, 0);
+        if !offsets.is_empty() {
+            attrs.push(netlink::Attribute::new_bytes(
+                Attribute::CsaCOffBeacon,
+                &offsets,
+            ));
+        }
+        if let Some(ref frame) = self.probe_response {
+            attrs.push(netlink::Attribute::new_bytes(Attribute::ProbeResp, frame));
+            let offsets = counter_offsets(frame, PROBE_RESPONSE_ELEMENTS_OFFSET);
+            if !offsets.is_empty() {
+                attrs.push(netlink::Attribute::new_bytes(
+                    Attribute::CsaCOffPresp,
+                    &offsets,
+                ));
+            }
+        }
+        message.append_attribute(nested_attribute(Attribute::CsaIes, &attrs));
+    }
+
+    /// Append the configuration to a start AP message
+    pub fn append_attributes(&self, message: &mut generic::Message, address: HardwareAddress) {
+        self.append_beacon_attributes(message, address);
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::BeaconInterval,
+            u32::from(self.beacon_interval),
+        ));
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::DtimPeriod,
+            u32::from(self.dtim_period),
+        ));
+        message.append_attribute(netlink::Attribute::new_bytes(Attribute::Ssid, &self.ssid));
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::HiddenSsid,
+            u32::from(self.hidden_ssid.clone()),
+        ));
+        if self.privacy && !self.crypto.has_privacy() {
+            message.append_attribute(flag_attribute(Attribute::Privacy));
+        }
+        self.crypto.append_attributes(message);
+        if let Some(ref auth_type) = self.auth_type {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::AuthType,
+                u32::from(auth_type.clone()),
+            ));
+        }
+        if let Some(ref chandef) = self.chandef {
+            chandef.append_attributes(message);
+        }
+        if let Some(timeout) = self.inactivity_timeout {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::InactivityTimeout,
+                timeout,
+            ));
+        }
+    }
+}
+
+/// BSS parameters of a running access point, parameters not given are left unchanged
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BssParameters {
+    cts_protection: Option<bool>,
+    short_preamble: Option<bool>,
+    short_slot_time: Option<bool>,
+    basic_rates: Option<Vec<u8>>,
+    ap_isolate: Option<bool>,
+    ht_opmode: Option<u16>,
+}
+
+impl BssParameters {
+    pub fn new() -> BssParameters {
+        BssParameters::default()
+    }
+
+    /// Use CTS protection for ERP stations
+    pub fn cts_protection(mut self, enable: bool) -> BssParameters {
+        self.cts_protection = Some(enable);
+        self
+    }
+
+    pub fn short_preamble(mut self, enable: bool) -> BssParameters {
+        self.short_preamble = Some(enable);
+        self
+    }
+
+    pub fn short_slot_time(mut self, enable: bool) -> BssParameters {
+        self.short_slot_time = Some(enable);
+        self
+    }
+
+    /// Basic rates in units of 500 kbit/s
+    pub fn basic_rates(mut self, rates: &[u8]) -> BssParameters {
+        self.basic_rates = Some(rates.to_vec());
+        self
+    }
+
+    /// Do not forward traffic between stations of the BSS
+    pub fn ap_isolate(mut self, enable: bool) -> BssParameters {
+        self.ap_isolate = Some(enable);
+        self
+    }
+
+    /// Operation mode field of the HT operation element
+    pub fn ht_opmode(mut self, opmode: u16) -> BssParameters {
+        self.ht_opmode = Some(opmode);
+        self
+    }
+
+    /// Check the parameters
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(ref rates) = self.basic_rates {
+            if rates.len() > MAX_BASIC_RATES {
+                return Err(
+                    io::Error::new(io::ErrorKind::InvalidInput, "Too many basic rates").into(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Append the parameters to a set BSS message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        if let Some(enable) = self.cts_protection {
+            message.append_attribute(netlink::Attribute::new(Attribute::BssCtsProt, enable as u8));
+        }
+        if let Some(enable) = self.short_preamble {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::BssShortPreamble,
+                enable as u8,
+            ));
+        }
+        if let Some(enable) = self.short_slot_time {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::BssShortSlotTime,
+                enable as u8,
+            ));
+        }
+        if let Some(ref rates) = self.basic_rates {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::BssBasicRates,
+                rates,
+            ));
+        }
+        if let Some(enable) = self.ap_isolate {
+            message.append_attribute(netlink::Attribute::new(Attribute::ApIsolate, enable as u8));
+        }
+        if let Some(opmode) = self.ht_opmode {
+            message.append_attribute(netlink::Attribute::new(Attribute::BssHtOpmode, opmode));
+        }
+    }
+}
+
+/// Channel number of a 2.4 GHz frequency in MHz, used in the DS parameter set element
+fn ds_channel(frequency: u32) -> Option<u8> {
+    if frequency < 2500 {
+        channel_number(frequency)
+    } else {
+        None
+    }
+}
+
+/// Offsets of the channel switch counters in the elements of a frame, packed as u16 values
+///
+/// The elements start at `start`, the offsets are relative to the start of the frame.
+fn counter_offsets(frame: &[u8], start: usize) -> Vec<u8> {
+    let announcement = u8::from(InformationElementId::ChannelSwitchAnnouncement);
+    let extended = u8::from(InformationElementId::ExtendedChannelSwitchAnnouncement);
+    let mut offsets = vec![];
+    let mut index = start;
+    while index + 2 <= frame.len() {
+        let identifier = frame[index];
+        let length = frame[index + 1] as usize;
+        let counter = if identifier == announcement && length >= 3 {
+            Some(index + 4)
+        } else if identifier == extended && length >= 4 {
+            Some(index + 5)
+        } else {
+            None
+        };
+        if let Some(offset) = counter {
+            let mut buffer = [0u8; 2];
+            NativeEndian::write_u16(&mut buffer, offset as u16);
+            offsets.extend_from_slice(&buffer);
+        }
+        index += 2 + length;
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+    use crate::unpack::LittleUnpack;
+
+    #[test]
+    fn test_beacon_head() {
+        let address = HardwareAddress::unpack_unchecked(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        let config = BeaconConfig::new(b"test")
+            .rates(&[0x82, 0x84, 0x8b, 0x96])
+            .chandef(Chandef::new(2437));
+        let head = config.beacon_head(address, &[0x82, 0x84, 0x8b, 0x96]);
+        assert_eq!(head.len(), 36 + 6 + 6 + 3);
+        assert_eq!(&head[10..16], &[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        assert_eq!(&head[32..34], &[100, 0]);
+        assert_eq!(&head[36..42], &[0, 4, b't', b'e', b's', b't']);
+        assert_eq!(&head[48..], &[3, 1, 6]);
+    }
+
+    #[test]
+    fn test_counter_offsets() {
+        let tail = [37, 3, 1, 36, 5, 221, 1, 0, 60, 4, 1, 128, 36, 5];
+        let offsets = counter_offsets(&tail, 0);
+        assert_eq!(offsets.len(), 4);
+        assert_eq!(NativeEndian::read_u16(&offsets[0..2]), 4);
+        assert_eq!(NativeEndian::read_u16(&offsets[2..4]), 13);
+    }
+
+    #[test]
+    fn test_channel_switch_attributes() {
+        let address = HardwareAddress::unpack_unchecked(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        let chandef = Chandef::new(2462);
+        let config = BeaconConfig::new(b"test")
+            .rates(&[0x82, 0x84, 0x8b, 0x96])
+            .chandef(chandef.clone());
+        let mut message =
+            generic::Message::new(0, Command::ChannelSwitch, netlink::MessageMode::Acknowledge);
+        config.append_channel_switch_attributes(&mut message, address, &chandef, 5, false);
+        let attrs = &message.attributes;
+        assert_eq!(attrs.len(), 1);
+        assert_eq!(attrs[0].identifier, u16::from(Attribute::CsaIes));
+        let (_, nested) = netlink::Attribute::unpack_all(&attrs[0].as_bytes());
+        assert_eq!(nested.len(), 3);
+        assert_eq!(nested[0].identifier, u16::from(Attribute::BeaconHead));
+        assert_eq!(nested[1].identifier, u16::from(Attribute::BeaconTail));
+        assert_eq!(&nested[1].as_bytes()[..5], &[37, 3, 0, 11, 5]);
+        assert_eq!(nested[2].identifier, u16::from(Attribute::CsaCOffBeacon));
+        assert_eq!(nested[2].as_u16().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_bss_parameters() {
+        let params = BssParameters::new()
+            .short_preamble(true)
+            .ap_isolate(false)
+            .ht_opmode(0x0004);
+        let mut message =
+            generic::Message::new(0, Command::SetBss, netlink::MessageMode::Acknowledge);
+        params.append_attributes(&mut message);
+        let attrs = &message.attributes;
+        assert_eq!(attrs.len(), 3);
+        assert_eq!(attrs[0].identifier, u16::from(Attribute::BssShortPreamble));
+        assert_eq!(attrs[0].as_bytes(), vec![1]);
+        assert_eq!(attrs[1].identifier, u16::from(Attribute::ApIsolate));
+        assert_eq!(attrs[1].as_bytes(), vec![0]);
+        assert_eq!(attrs[2].identifier, u16::from(Attribute::BssHtOpmode));
+        assert_eq!(NativeEndian::read_u16(&attrs[2].as_bytes()), 4);
+    }
+}
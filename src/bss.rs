@@ -0,0 +1,285 @@
+//! ## Basic Service Set
+//!
+//! Scan results as reported by nl80211, one `Bss` per access point, mesh point or IBSS seen
+//! by the wireless device.
+
+use std::fmt;
+use std::io;
+
+use crate::attributes::{Attribute, BssAttribute};
+use crate::information_element::InformationElement;
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{ConvertFrom, Error, HardwareAddress};
+
+/// Relation between the local device and a basic service set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BssStatus {
+    /// Authenticated with the BSS
+    Authenticated,
+    /// Associated with the BSS
+    Associated,
+    /// Joined the IBSS
+    IbssJoined,
+}
+
+impl ConvertFrom<u32> for BssStatus {
+    fn convert_from(value: u32) -> Option<BssStatus> {
+        match value {
+            0 => Some(BssStatus::Authenticated),
+            1 => Some(BssStatus::Associated),
+            2 => Some(BssStatus::IbssJoined),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BssStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BssStatus::Authenticated => write!(f, "Authenticated"),
+            BssStatus::Associated => write!(f, "Associated"),
+            BssStatus::IbssJoined => write!(f, "Joined"),
+        }
+    }
+}
+
+/// Basic service set (BSS) scan result
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bss {
+    /// BSSID of the BSS
+    pub bssid: HardwareAddress,
+    /// Frequency in MHz
+    pub frequency: u32,
+    /// Frequency offset in kHz
+    pub frequency_offset: Option<u32>,
+    /// Timing synchronization function value from the last received frame
+    pub tsf: Option<u64>,
+    /// Timing synchronization function value from the last received beacon
+    pub beacon_tsf: Option<u64>,
+    /// Beacon interval in time units (1024 µs)
+    pub beacon_interval: Option<u16>,
+    /// Capability field from the beacon or probe response
+    pub capability: Option<u16>,
+    /// Signal strength in mBm (100 * dBm)
+    pub signal_mbm: Option<i32>,
+    /// Signal strength in unspecified units, scaled 0 - 100
+    pub signal_unspec: Option<u8>,
+    /// Milliseconds since the BSS was last seen
+    pub seen_ms_ago: Option<u32>,
+    /// Time of last reception in CLOCK_BOOTTIME, nanoseconds
+    pub last_seen_boottime: Option<u64>,
+    /// Relation between the local device and the BSS
+    pub status: Option<BssStatus>,
+    /// Timing synchronization function of the BSS given by `parent_bssid` at the time this BSS
+    /// was seen
+    pub parent_tsf: Option<u64>,
+    /// BSSID of the BSS used as reference for `parent_tsf`
+    pub parent_bssid: Option<HardwareAddress>,
+    /// Per chain signal strength in dBm
+    pub chain_signal: Vec<i8>,
+    /// Channel width used for scanning
+    pub scan_width: Option<u32>,
+    /// The information elements were received in a probe response
+    pub probe_response_data: bool,
+    information_elements: Vec<u8>,
+    beacon_information_elements: Vec<u8>,
+}
+
+impl Bss {
+    /// Parse the nested BSS attributes
+    pub fn from_attributes(attributes: &[netlink::Attribute]) -> Result<Bss, Error> {
+        let mut bssid = None;
+        let mut frequency = None;
+        let mut frequency_offset = None;
+        let mut tsf = None;
+        let mut beacon_tsf = None;
+        let mut beacon_interval = None;
+        let mut capability = None;
+        let mut signal_mbm = None;
+        let mut signal_unspec = None;
+        let mut seen_ms_ago = None;
+        let mut last_seen_boottime = None;
+        let mut status = None;
+        let mut parent_tsf = None;
+        let mut parent_bssid = None;
+        let mut chain_signal = vec![];
+        let mut scan_width = None;
+        let mut probe_response_data = false;
+        let mut information_elements = vec![];
+        let mut beacon_information_elements = vec![];
+        for attr in attributes {
+            let identifier = BssAttribute::convert_from(attr.identifier);
+            if let Some(identifier) = identifier {
+                match identifier {
+                    BssAttribute::Bssid => {
+                        bssid = Some(attr.as_hardware_address()?);
+                    }
+                    BssAttribute::Frequency => {
+                        frequency = Some(attr.as_u32()?);
+                    }
+                    BssAttribute::FrequencyOffset => {
+                        frequency_offset = Some(attr.as_u32()?);
+                    }
+                    BssAttribute::Tsf => {
+                        tsf = Some(attr.as_u64()?);
+                    }
+                    BssAttribute::BeaconTsf => {
+                        beacon_tsf = Some(attr.as_u64()?);
+                    }
+                    BssAttribute::BeaconInterval => {
+                        beacon_interval = Some(attr.as_u16()?);
+                    }
+                    BssAttribute::Capability => {
+                        capability = Some(attr.as_u16()?);
+                    }
+                    BssAttribute::SignalMbm => {
+                        signal_mbm = Some(attr.as_i32()?);
+                    }
+                    BssAttribute::SignalUnspec => {
+                        signal_unspec = Some(attr.as_u8()?);
+                    }
+                    BssAttribute::SeenMsAgo => {
+                        seen_ms_ago = Some(attr.as_u32()?);
+                    }
+                    BssAttribute::LastSeenBootTime => {
+                        last_seen_boottime = Some(attr.as_u64()?);
+                    }
+                    BssAttribute::Status => {
+                        status = BssStatus::convert_from(attr.as_u32()?);
+                    }
+                    BssAttribute::ParentTsf => {
+                        parent_tsf = Some(attr.as_u64()?);
+                    }
+                    BssAttribute::ParentBssid => {
+                        parent_bssid = Some(attr.as_hardware_address()?);
+                    }
+                    BssAttribute::ChainSignal => {
+                        let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                        for attr in attrs {
+                            chain_signal.push(attr.as_i8()?);
+                        }
+                    }
+                    BssAttribute::ChanWidth => {
+                        scan_width = Some(attr.as_u32()?);
+                    }
+                    BssAttribute::PrespData => {
+                        probe_response_data = true;
+                    }
+                    BssAttribute::InformationElements => {
+                        information_elements = attr.as_bytes();
+                    }
+                    BssAttribute::BeaconIes => {
+                        beacon_information_elements = attr.as_bytes();
+                    }
+                    BssAttribute::Pad => (),
+                }
+            }
+        }
+        match (bssid, frequency) {
+            (Some(bssid), Some(frequency)) => Ok(Bss {
+                bssid,
+                frequency,
+                frequency_offset,
+                tsf,
+                beacon_tsf,
+                beacon_interval,
+                capability,
+                signal_mbm,
+                signal_unspec,
+                seen_ms_ago,
+                last_seen_boottime,
+                status,
+                parent_tsf,
+                parent_bssid,
+                chain_signal,
+                scan_width,
+                probe_response_data,
+                information_elements,
+                beacon_information_elements,
+            }),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid BSS").into()),
+        }
+    }
+
+    /// Parse the BSS from a scan result message
+    pub fn from_message(message: &generic::Message) -> Result<Bss, Error> {
+        for attr in &message.attributes {
+            if let Some(Attribute::Bss) = Attribute::convert_from(attr.identifier) {
+                let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                return Bss::from_attributes(&attrs);
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::NotFound, "BSS Not Found").into())
+    }
+
+    /// Signal strength in dBm
+    pub fn signal_dbm(&self) -> Option<f64> {
+        self.signal_mbm.map(|s| f64::from(s) / 100.0)
+    }
+
+    /// Information elements from the most recently received beacon or probe response
+    pub fn information_elements(&self) -> Result<Vec<InformationElement<'_>>, Error> {
+        InformationElement::parse_all(&self.information_elements)
+    }
+
+    /// Information elements from the most recently received beacon
+    pub fn beacon_information_elements(&self) -> Result<Vec<InformationElement<'_>>, Error> {
+        InformationElement::parse_all(&self.beacon_information_elements)
+    }
+
+    /// The SSID advertised by the BSS, taken from the probe response or the beacon
+    pub fn ssid(&self) -> Option<String> {
+        let find_ssid = |ies: Vec<InformationElement>| {
+            ies.into_iter().find_map(|ie| match ie {
+                InformationElement::Ssid(ie) => Some(ie.ssid),
+                _ => None,
+            })
+        };
+        self.information_elements()
+            .ok()
+            .and_then(find_ssid)
+            .or_else(|| self.beacon_information_elements().ok().and_then(find_ssid))
+    }
+}
+
+impl fmt::Display for Bss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BSSID: {} Frequency: {} MHz", self.bssid, self.frequency)?;
+        if let Some(signal) = self.signal_dbm() {
+            write!(f, " Signal: {:.2} dBm", signal)?;
+        }
+        if let Some(ssid) = self.ssid() {
+            write!(f, " SSID: {}", ssid)?;
+        }
+        if let Some(ref status) = self.status {
+            write!(f, " Status: {}", status)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bss() {
+        let bytes = [
+            10, 0, 1, 0, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0, 0, 8, 0, 2, 0, 0x6c, 0x09, 0, 0, 6,
+            0, 4, 0, 100, 0, 0, 0, 6, 0, 5, 0, 0x11, 0x04, 0, 0, 10, 0, 6, 0, 0, 4, b't', b'e',
+            b's', b't', 0, 0, 8, 0, 7, 0, 0x84, 0xea, 0xff, 0xff, 8, 0, 9, 0, 1, 0, 0, 0,
+        ];
+        let (_, attrs) = netlink::Attribute::unpack_all(&bytes);
+        let bss = Bss::from_attributes(&attrs).unwrap();
+        let bssid = [0x02, 0x00, 0x00, 0x00, 0x00, 0x01];
+        assert_eq!(bss.bssid, HardwareAddress::from(&bssid[..]));
+        assert_eq!(bss.frequency, 2412);
+        assert_eq!(bss.beacon_interval, Some(100));
+        assert_eq!(bss.capability, Some(0x0411));
+        assert_eq!(bss.signal_dbm(), Some(-55.0));
+        assert_eq!(bss.status, Some(BssStatus::Associated));
+        assert_eq!(bss.ssid(), Some(String::from("test")));
+        assert!(Bss::from_attributes(&attrs[1..]).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! ## Channel definition
+//!
+//! Control frequency, width and center frequencies of an operating channel.
+
+use std::fmt;
+use std::io;
+
+use crate::attributes::{Attribute, ChannelWidth};
+use crate::commands::Command;
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{ConvertFrom, Error};
+
+/// Channel number of a frequency in MHz
+pub fn channel_number(frequency: u32) -> Option<u8> {
+    let channel = match frequency {
+        2484 => 14,
+        2412..=2472 => (frequency - 2407) / 5,
+        4910..=4980 => (frequency - 4000) / 5,
+        5000..=5900 => (frequency - 5000) / 5,
+        5935 => 2,
+        5955..=7115 => (frequency - 5950) / 5,
+        58320..=70200 => (frequency - 56160) / 2160,
+        _ => return None,
+    };
+    Some(channel as u8)
+}
+
+/// The frequency in MHz is in the sub 1 GHz (S1G) band
+fn is_sub_1ghz(frequency: u32) -> bool {
+    (863..=930).contains(&frequency)
+}
+
+/// Channel definition
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chandef {
+    /// Frequency of the control channel in MHz
+    pub control_freq: u32,
+    /// Offset of the control channel frequency in kHz
+    pub freq_offset: u32,
+    pub width: ChannelWidth,
+    /// Center frequency of the channel in MHz
+    pub center_freq1: u32,
+    /// Offset of the center frequency in kHz
+    pub center_freq1_offset: u32,
+    /// Center frequency of the second segment in MHz, only used with 80+80 MHz
+    pub center_freq2: Option<u32>,
+}
+
+impl Chandef {
+    /// A 20 MHz channel without HT
+    pub fn new(control_freq: u32) -> Chandef {
+        Chandef {
+            control_freq,
+            freq_offset: 0,
+            width: ChannelWidth::NoHt20,
+            center_freq1: control_freq,
+            center_freq1_offset: 0,
+            center_freq2: None,
+        }
+    }
+
+    /// A channel of the given width centered on `center_freq1`
+    pub fn with_width(control_freq: u32, width: ChannelWidth, center_freq1: u32) -> Chandef {
+        Chandef {
+            control_freq,
+            freq_offset: 0,
+            width,
+            center_freq1,
+            center_freq1_offset: 0,
+            center_freq2: None,
+        }
+    }
+
+    /// An 80+80 MHz channel
+    pub fn with_segments(control_freq: u32, center_freq1: u32, center_freq2: u32) -> Chandef {
+        Chandef {
+            control_freq,
+            freq_offset: 0,
+            width: ChannelWidth::Width80P80,
+            center_freq1,
+            center_freq1_offset: 0,
+            center_freq2: Some(center_freq2),
+        }
+    }
+
+    /// Parse the channel definition from the attributes of a message
+    pub fn from_attributes(attributes: &[netlink::Attribute]) -> Option<Chandef> {
+        let mut control_freq = None;
+        let mut freq_offset = 0;
+        let mut width = None;
+        let mut center_freq1 = None;
+        let mut center_freq1_offset = 0;
+        let mut center_freq2 = None;
+        for attr in attributes {
+            match Attribute::convert_from(attr.identifier) {
+                Some(Attribute::WiphyFreq) => {
+                    control_freq = attr.as_u32().ok();
+                }
+                Some(Attribute::WiphyFreqOffset) => {
+                    freq_offset = attr.as_u32().unwrap_or(0);
+                }
+                Some(Attribute::ChannelWidth) => {
+                    width = attr.as_u32().ok().and_then(ChannelWidth::convert_from);
+                }
+                Some(Attribute::CenterFreq1) => {
+                    center_freq1 = attr.as_u32().ok();
+                }
+                Some(Attribute::CenterFreq1Offset) => {
+                    center_freq1_offset = attr.as_u32().unwrap_or(0);
+                }
+                Some(Attribute::CenterFreq2) => {
+                    center_freq2 = attr.as_u32().ok();
+                }
+                _ => (),
+            }
+        }
+        let control_freq = control_freq?;
+        Some(Chandef {
+            control_freq,
+            freq_offset,
+            width: width.unwrap_or(ChannelWidth::NoHt20),
+            center_freq1: center_freq1.unwrap_or(control_freq),
+            center_freq1_offset,
+            center_freq2,
+        })
+    }
+
+    /// Parse the channel definition from a message
+    pub fn from_message(message: &generic::Message) -> Option<Chandef> {
+        Chandef::from_attributes(&message.attributes)
+    }
+
+    /// Check that the center frequencies match the width and the band of the control channel
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if channel_number(self.control_freq).is_none() && !is_sub_1ghz(self.control_freq) {
+            return invalid("Invalid control frequency");
+        }
+        if self.freq_offset >= 1000 || self.center_freq1_offset >= 1000 {
+            return invalid("Invalid frequency offset");
+        }
+        let band_supports_width = match self.width {
+            ChannelWidth::NoHt20
+            | ChannelWidth::Width20
+            | ChannelWidth::Width40
+            | ChannelWidth::Width5
+            | ChannelWidth::Width10 => !is_sub_1ghz(self.control_freq),
+            ChannelWidth::Width80 | ChannelWidth::Width80P80 | ChannelWidth::Width160 => {
+                self.control_freq > 5000 && self.control_freq < 7200
+            }
+            ChannelWidth::Width320 => self.control_freq > 5900 && self.control_freq < 7200,
+            ChannelWidth::Width1
+            | ChannelWidth::Width2
+            | ChannelWidth::Width4
+            | ChannelWidth::Width8
+            | ChannelWidth::Width16 => is_sub_1ghz(self.control_freq),
+        };
+        if !band_supports_width {
+            return invalid("Channel width not supported on the band");
+        }
+        if self.center_freq2.is_some() != (self.width == ChannelWidth::Width80P80) {
+            return invalid("Second center frequency only valid with 80+80 MHz");
+        }
+        let width = self.width_mhz();
+        let control_khz = self.control_freq * 1000 + self.freq_offset;
+        let center_khz = self.center_freq1 * 1000 + self.center_freq1_offset;
+        match self.width {
+            ChannelWidth::Width40
+            | ChannelWidth::Width80
+            | ChannelWidth::Width80P80
+            | ChannelWidth::Width160
+            | ChannelWidth::Width320 => {
+                // The control channel must be one of the 20 MHz channels of the first segment
+                let lowest = self.center_freq1 + 10;
+                let control = self.control_freq + width / 2;
+                if !(0..width / 20).any(|index| lowest + index * 20 == control) {
+                    return invalid("Control channel not within the channel");
+                }
+            }
+            ChannelWidth::Width1 => {
+                if center_khz != control_khz {
+                    return invalid("Center frequency must equal the control frequency");
+                }
+            }
+            ChannelWidth::Width2
+            | ChannelWidth::Width4
+            | ChannelWidth::Width8
+            | ChannelWidth::Width16 => {
+                // The control channel must be one of the 1 MHz channels, in kHz
+                let lowest = center_khz + 500;
+                let control = control_khz + width * 500;
+                if !(0..width).any(|index| lowest + index * 1000 == control) {
+                    return invalid("Control channel not within the channel");
+                }
+            }
+            _ => {
+                if self.center_freq1 != self.control_freq {
+                    return invalid("Center frequency must equal the control frequency");
+                }
+            }
+        }
+        if let Some(center_freq2) = self.center_freq2 {
+            if center_freq2.max(self.center_freq1) - center_freq2.min(self.center_freq1) <= 80 {
+                return invalid("Segments of 80+80 MHz channel overlap or are adjacent");
+            }
+        }
+        Ok(())
+    }
+
+    /// Width of the channel in MHz, the width of a segment with 80+80 MHz
+    pub fn width_mhz(&self) -> u32 {
+        match self.width {
+            ChannelWidth::NoHt20 | ChannelWidth::Width20 => 20,
+            ChannelWidth::Width40 => 40,
+            ChannelWidth::Width80 | ChannelWidth::Width80P80 => 80,
+            ChannelWidth::Width160 => 160,
+            ChannelWidth::Width320 => 320,
+            ChannelWidth::Width5 => 5,
+            ChannelWidth::Width10 => 10,
+            ChannelWidth::Width1 => 1,
+            ChannelWidth::Width2 => 2,
+            ChannelWidth::Width4 => 4,
+            ChannelWidth::Width8 => 8,
+            ChannelWidth::Width16 => 16,
+        }
+    }
+
+    /// Append the channel definition to a message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::WiphyFreq,
+            self.control_freq,
+        ));
+        if self.freq_offset != 0 {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::WiphyFreqOffset,
+                self.freq_offset,
+            ));
+        }
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::ChannelWidth,
+            u32::from(self.width.clone()),
+        ));
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::CenterFreq1,
+            self.center_freq1,
+        ));
+        if self.center_freq1_offset != 0 {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::CenterFreq1Offset,
+                self.center_freq1_offset,
+            ));
+        }
+        if let Some(frequency) = self.center_freq2 {
+            message.append_attribute(netlink::Attribute::new(Attribute::CenterFreq2, frequency));
+        }
+    }
+}
+
+impl fmt::Display for Chandef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.control_freq)?;
+        if self.freq_offset != 0 {
+            write!(f, ".{:03}", self.freq_offset)?;
+        }
+        write!(f, " MHz Width: {:?}", self.width)?;
+        if self.center_freq1 != self.control_freq || self.center_freq1_offset != self.freq_offset {
+            write!(f, " Center: {}", self.center_freq1)?;
+            if self.center_freq1_offset != 0 {
+                write!(f, ".{:03}", self.center_freq1_offset)?;
+            }
+            write!(f, " MHz")?;
+        }
+        if let Some(center_freq2) = self.center_freq2 {
+            write!(f, " Center 2: {} MHz", center_freq2)?;
+        }
+        Ok(())
+    }
+}
+
+/// Channel switch notification
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChannelSwitchEvent {
+    /// A channel switch to the channel was started, switching after the given number of beacons
+    Started(Chandef, Option<u32>),
+    /// The channel was switched
+    Completed(Chandef),
+}
+
+impl ChannelSwitchEvent {
+    /// Parse a channel switch event, `None` if the message is some other command
+    pub fn from_message(message: &generic::Message) -> Option<ChannelSwitchEvent> {
+        match Command::convert_from(message.command)? {
+            Command::ChannelSwitchStartedNotify => {
+                let count = message
+                    .attributes
+                    .iter()
+                    .find(|attr| {
+                        Attribute::convert_from(attr.identifier) == Some(Attribute::ChSwitchCount)
+                    })
+                    .and_then(|attr| attr.as_u32().ok());
+                let chandef = Chandef::from_attributes(&message.attributes)?;
+                Some(ChannelSwitchEvent::Started(chandef, count))
+            }
+            Command::ChannelSwitchNotify => {
+                let chandef = Chandef::from_attributes(&message.attributes)?;
+                Some(ChannelSwitchEvent::Completed(chandef))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_chandef() {
+        assert!(Chandef::new(2412).validate().is_ok());
+        assert!(Chandef::with_width(2412, ChannelWidth::Width40, 2422)
+            .validate()
+            .is_ok());
+        assert!(Chandef::with_width(5180, ChannelWidth::Width80, 5210)
+            .validate()
+            .is_ok());
+        assert!(Chandef::with_width(5240, ChannelWidth::Width80, 5210)
+            .validate()
+            .is_ok());
+        assert!(Chandef::with_width(5180, ChannelWidth::Width80, 5200)
+            .validate()
+            .is_err());
+        assert!(Chandef::with_width(2412, ChannelWidth::Width80, 2442)
+            .validate()
+            .is_err());
+        assert!(Chandef::with_segments(5180, 5210, 5290).validate().is_err());
+        assert!(Chandef::with_segments(5180, 5210, 5775).validate().is_ok());
+        let mut chandef = Chandef::with_width(903, ChannelWidth::Width4, 904);
+        chandef.freq_offset = 500;
+        assert!(chandef.validate().is_ok());
+        chandef.control_freq = 906;
+        assert!(chandef.validate().is_err());
+        assert!(Chandef::with_width(500, ChannelWidth::Width1, 500)
+            .validate()
+            .is_err());
+        let mut chandef = Chandef::with_width(902, ChannelWidth::Width1, 902);
+        chandef.freq_offset = 500;
+        assert!(chandef.validate().is_err());
+        chandef.center_freq1_offset = 500;
+        assert!(chandef.validate().is_ok());
+    }
+}
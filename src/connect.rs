@@ -0,0 +1,584 @@
+//! ## Connect
+//!
+//! Parameters for connecting to a BSS, letting the kernel or the driver handle authentication
+//! and association.
+
+use std::fmt;
+use std::io;
+
+use byteorder::{ByteOrder, NativeEndian};
+
+use crate::attributes::{
+    Attribute, AuthenticationType, Band, BssSelectAttribute, ManagementFrameProtection,
+    TimeoutReason,
+};
+use crate::commands::Command;
+use crate::information_element::{AuthenticationKeyManagement, CipherSuite};
+use crate::pack::{flag_attribute, nested_attribute};
+use crate::status::{ReasonCode, StatusCode};
+use crate::wireless_phy::{ExtendedFeaturesFlags, FeatureFlags, WirelessPhy};
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{ConvertFrom, Error, HardwareAddress};
+
+const MAX_SSID_LENGTH: usize = 32;
+
+/// Pack suite selectors as an array of u32 in the byte order used by nl80211
+fn pack_suites<I: Iterator<Item = u32>>(suites: I) -> Vec<u8> {
+    let mut data = vec![];
+    for suite in suites {
+        let mut buffer = [0u8; 4];
+        NativeEndian::write_u32(&mut buffer, suite.to_be());
+        data.extend_from_slice(&buffer);
+    }
+    data
+}
+
+bitflags! {
+    /// WPA versions
+    pub struct WpaVersions: u32 {
+        const WPA1 = 1 << 0;
+        const WPA2 = 1 << 1;
+        const WPA3 = 1 << 2;
+    }
+}
+
+bitflags! {
+    /// Control port (EAPOL) handling flags
+    pub struct ControlPortFlags: u32 {
+        /// Allow unencrypted control port frames
+        const NO_ENCRYPT    = 1 << 0;
+        /// Transmit and receive control port frames over nl80211 instead of the network
+        /// interface
+        const OVER_NL80211  = 1 << 1;
+        /// Do not pass pre-authentication frames over nl80211
+        const NO_PREAUTH    = 1 << 2;
+    }
+}
+
+/// BSS selection behaviour of the driver, used when connecting and roaming
+#[derive(Clone, Debug, PartialEq)]
+pub enum BssSelect {
+    /// Prefer the BSS with the strongest signal
+    Rssi,
+    /// Prefer BSSs on the band
+    BandPreference(Band),
+    /// Adjust the signal of BSSs on the band by the given dB when comparing
+    RssiAdjust(Band, i8),
+}
+
+impl BssSelect {
+    fn to_attribute(&self) -> netlink::Attribute {
+        let attr = match *self {
+            BssSelect::Rssi => flag_attribute(BssSelectAttribute::Rssi),
+            BssSelect::BandPreference(ref band) => netlink::Attribute::new(
+                BssSelectAttribute::BandPreference,
+                u32::from(u16::from(band.clone())),
+            ),
+            BssSelect::RssiAdjust(ref band, delta) => {
+                // struct nl80211_bss_select_rssi_adjust
+                let data = [u16::from(band.clone()) as u8, delta as u8];
+                netlink::Attribute::new_bytes(BssSelectAttribute::RssiAdjust, &data)
+            }
+        };
+        nested_attribute(Attribute::BssSelect, &[attr])
+    }
+}
+
+/// Cipher, key management and control port settings shared by connect and associate requests
+#[derive(Clone, Default)]
+pub(crate) struct CryptoSettings {
+    pub wpa_versions: Option<WpaVersions>,
+    pub pairwise_ciphers: Vec<CipherSuite>,
+    pub group_cipher: Option<CipherSuite>,
+    pub akm_suites: Vec<AuthenticationKeyManagement>,
+    pub control_port: Option<(Option<u16>, ControlPortFlags)>,
+}
+
+impl CryptoSettings {
+    /// Check the settings against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        let extended_features = phy.extended_features();
+        if let Some((_, flags)) = self.control_port {
+            if flags.contains(ControlPortFlags::OVER_NL80211)
+                && !extended_features.contains(ExtendedFeaturesFlags::CONTROL_PORT_OVER_NL80211)
+            {
+                return invalid("Control port over nl80211 not supported");
+            }
+            if flags.contains(ControlPortFlags::NO_PREAUTH)
+                && !extended_features.contains(ExtendedFeaturesFlags::CONTROL_PORT_NO_PREAUTH)
+            {
+                return invalid("Control port without pre-authentication not supported");
+            }
+        }
+        Ok(())
+    }
+
+    /// Privacy is required when WPA versions or ciphers are given
+    pub fn has_privacy(&self) -> bool {
+        self.wpa_versions.is_some()
+            || !self.pairwise_ciphers.is_empty()
+            || self.group_cipher.is_some()
+    }
+
+    /// Append the settings to a message, privacy is requested when WPA versions or ciphers are
+    /// given
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        if self.has_privacy() {
+            message.append_attribute(flag_attribute(Attribute::Privacy));
+        }
+        if let Some(versions) = self.wpa_versions {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::WpaVersions,
+                versions.bits(),
+            ));
+        }
+        if !self.pairwise_ciphers.is_empty() {
+            let data = pack_suites(self.pairwise_ciphers.iter().cloned().map(u32::from));
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::CipherSuitesPairwise,
+                &data,
+            ));
+        }
+        if let Some(ref cipher) = self.group_cipher {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::CipherSuiteGroup,
+                u32::from(cipher.clone()).to_be(),
+            ));
+        }
+        if !self.akm_suites.is_empty() {
+            let data = pack_suites(self.akm_suites.iter().cloned().map(u32::from));
+            message.append_attribute(netlink::Attribute::new_bytes(Attribute::AkmSuites, &data));
+        }
+        if let Some((ethertype, flags)) = self.control_port {
+            message.append_attribute(flag_attribute(Attribute::ControlPort));
+            if let Some(ethertype) = ethertype {
+                message.append_attribute(netlink::Attribute::new(
+                    Attribute::ControlPortEthertype,
+                    ethertype,
+                ));
+            }
+            if flags.contains(ControlPortFlags::NO_ENCRYPT) {
+                message.append_attribute(flag_attribute(Attribute::ControlPortNoEncrypt));
+            }
+            if flags.contains(ControlPortFlags::OVER_NL80211) {
+                message.append_attribute(flag_attribute(Attribute::ControlPortFrameOverNl80211));
+            }
+            if flags.contains(ControlPortFlags::NO_PREAUTH) {
+                message.append_attribute(flag_attribute(Attribute::ControlPortNoPreauthentication));
+            }
+        }
+    }
+}
+
+/// Connect request parameters
+///
+/// Privacy is requested when WPA versions or ciphers are given.
+#[derive(Clone, Default)]
+pub struct ConnectParams {
+    ssid: Vec<u8>,
+    bssid: Option<HardwareAddress>,
+    bssid_hint: Option<HardwareAddress>,
+    frequency: Option<u32>,
+    frequency_hint: Option<u32>,
+    auth_type: Option<AuthenticationType>,
+    crypto: CryptoSettings,
+    pmk: Option<Vec<u8>>,
+    sae_password: Option<Vec<u8>>,
+    mfp: Option<ManagementFrameProtection>,
+    information_elements: Vec<u8>,
+    bss_select: Option<BssSelect>,
+}
+
+impl ConnectParams {
+    /// Connect to a BSS with the given SSID
+    pub fn new(ssid: &[u8]) -> ConnectParams {
+        ConnectParams {
+            ssid: ssid.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    /// Only connect to the BSS with the given BSSID
+    pub fn bssid(mut self, bssid: HardwareAddress) -> ConnectParams {
+        self.bssid = Some(bssid);
+        self
+    }
+
+    /// Prefer the BSS with the given BSSID, the driver may still select another BSS
+    pub fn bssid_hint(mut self, bssid: HardwareAddress) -> ConnectParams {
+        self.bssid_hint = Some(bssid);
+        self
+    }
+
+    /// Only connect on the given frequency in MHz
+    pub fn frequency(mut self, frequency: u32) -> ConnectParams {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    /// Prefer the given frequency in MHz, the driver may still select another frequency
+    pub fn frequency_hint(mut self, frequency: u32) -> ConnectParams {
+        self.frequency_hint = Some(frequency);
+        self
+    }
+
+    /// Authentication type, the driver selects one if not given
+    pub fn auth_type(mut self, auth_type: AuthenticationType) -> ConnectParams {
+        self.auth_type = Some(auth_type);
+        self
+    }
+
+    pub fn wpa_versions(mut self, versions: WpaVersions) -> ConnectParams {
+        self.crypto.wpa_versions = Some(versions);
+        self
+    }
+
+    /// Add a pairwise cipher suite
+    pub fn pairwise_cipher(mut self, cipher: CipherSuite) -> ConnectParams {
+        self.crypto.pairwise_ciphers.push(cipher);
+        self
+    }
+
+    pub fn group_cipher(mut self, cipher: CipherSuite) -> ConnectParams {
+        self.crypto.group_cipher = Some(cipher);
+        self
+    }
+
+    /// Add an authentication and key management suite
+    pub fn akm_suite(mut self, akm: AuthenticationKeyManagement) -> ConnectParams {
+        self.crypto.akm_suites.push(akm);
+        self
+    }
+
+    /// Pairwise master key for drivers doing the 4-way handshake
+    ///
+    /// For WPA2-PSK this is the key derived from the passphrase and the SSID.
+    pub fn pmk(mut self, pmk: &[u8]) -> ConnectParams {
+        self.pmk = Some(pmk.to_vec());
+        self
+    }
+
+    /// Password for drivers doing SAE authentication
+    pub fn sae_password(mut self, password: &[u8]) -> ConnectParams {
+        self.sae_password = Some(password.to_vec());
+        self
+    }
+
+    /// Use of management frame protection
+    pub fn mfp(mut self, mfp: ManagementFrameProtection) -> ConnectParams {
+        self.mfp = Some(mfp);
+        self
+    }
+
+    /// Information elements to add to the association request
+    pub fn information_elements(mut self, data: &[u8]) -> ConnectParams {
+        self.information_elements = data.to_vec();
+        self
+    }
+
+    /// Block data traffic until the control port is authorized
+    ///
+    /// `ethertype` defaults to EAPOL (0x888e).
+    pub fn control_port(
+        mut self,
+        ethertype: Option<u16>,
+        flags: ControlPortFlags,
+    ) -> ConnectParams {
+        self.crypto.control_port = Some((ethertype, flags));
+        self
+    }
+
+    pub fn bss_select(mut self, select: BssSelect) -> ConnectParams {
+        self.bss_select = Some(select);
+        self
+    }
+
+    /// Check the request against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        let extended_features = phy.extended_features();
+        if self.ssid.is_empty() || self.ssid.len() > MAX_SSID_LENGTH {
+            return invalid("Invalid SSID");
+        }
+        if let Some(ref pmk) = self.pmk {
+            if pmk.len() != 32 && pmk.len() != 48 {
+                return invalid("Invalid PMK length");
+            }
+            if !extended_features.contains(ExtendedFeaturesFlags::FOUR_WAY_HANDSHAKE_STA_PSK) {
+                return invalid("4-way handshake offload not supported");
+            }
+        }
+        if self.sae_password.is_some()
+            && !extended_features.contains(ExtendedFeaturesFlags::SAE_OFFLOAD)
+        {
+            return invalid("SAE offload not supported");
+        }
+        if self.auth_type == Some(AuthenticationType::Sae)
+            && !phy.features().contains(FeatureFlags::SAE)
+            && !extended_features.contains(ExtendedFeaturesFlags::SAE_OFFLOAD)
+        {
+            return invalid("SAE not supported");
+        }
+        if self.mfp == Some(ManagementFrameProtection::Optional)
+            && !extended_features.contains(ExtendedFeaturesFlags::MFP_OPTIONAL)
+        {
+            return invalid("Optional MFP not supported");
+        }
+        self.crypto.validate(phy)
+    }
+
+    /// Append the request parameters to a connect message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        message.append_attribute(netlink::Attribute::new_bytes(Attribute::Ssid, &self.ssid));
+        if let Some(bssid) = self.bssid {
+            message.append_attribute(netlink::Attribute::new(Attribute::Mac, bssid));
+        }
+        if let Some(bssid) = self.bssid_hint {
+            message.append_attribute(netlink::Attribute::new(Attribute::MacHint, bssid));
+        }
+        if let Some(frequency) = self.frequency {
+            message.append_attribute(netlink::Attribute::new(Attribute::WiphyFreq, frequency));
+        }
+        if let Some(frequency) = self.frequency_hint {
+            message.append_attribute(netlink::Attribute::new(Attribute::WiphyFreqHint, frequency));
+        }
+        if let Some(ref auth_type) = self.auth_type {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::AuthType,
+                u32::from(auth_type.clone()),
+            ));
+        }
+        self.crypto.append_attributes(message);
+        if let Some(ref pmk) = self.pmk {
+            message.append_attribute(netlink::Attribute::new_bytes(Attribute::Pmk, pmk));
+        }
+        if let Some(ref password) = self.sae_password {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::SaePassword,
+                password,
+            ));
+        }
+        if let Some(ref mfp) = self.mfp {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::UseMfp,
+                u32::from(mfp.clone()),
+            ));
+        }
+        if !self.information_elements.is_empty() {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::InformationElement,
+                &self.information_elements,
+            ));
+        }
+        if let Some(ref select) = self.bss_select {
+            message.append_attribute(select.to_attribute());
+        }
+    }
+}
+
+/// Outcome of a connection attempt, reported with a connect event
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConnectResult {
+    /// BSSID of the BSS, not always reported for failed attempts
+    pub bssid: Option<HardwareAddress>,
+    /// Status code of the association response
+    pub status: StatusCode,
+    /// No response was received from the BSS
+    pub timed_out: bool,
+    /// Step that timed out when `timed_out` is set
+    pub timeout_reason: Option<TimeoutReason>,
+    /// Information elements of the association request
+    pub request_ies: Vec<u8>,
+    /// Information elements of the association response
+    pub response_ies: Vec<u8>,
+    /// Frequency of the BSS in MHz
+    pub frequency: Option<u32>,
+    /// The 4-way handshake was completed by the driver
+    pub port_authorized: bool,
+}
+
+impl ConnectResult {
+    /// The connection was established
+    pub fn is_success(&self) -> bool {
+        !self.timed_out && self.status.is_success()
+    }
+}
+
+impl fmt::Display for ConnectResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_success() {
+            write!(f, "Connected")?;
+        } else {
+            write!(f, "Connect failed")?;
+        }
+        if let Some(ref bssid) = self.bssid {
+            write!(f, " to {}", bssid)?;
+        }
+        if let Some(frequency) = self.frequency {
+            write!(f, " on {} MHz", frequency)?;
+        }
+        if self.timed_out {
+            match self.timeout_reason {
+                Some(ref reason) => write!(f, ": timed out ({})", reason),
+                None => write!(f, ": timed out"),
+            }
+        } else if !self.status.is_success() {
+            write!(f, ": {}", self.status)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The connection moved to another BSS of the same ESS
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoamEvent {
+    /// BSSID of the new BSS
+    pub bssid: Option<HardwareAddress>,
+    /// Frequency of the new BSS in MHz
+    pub frequency: Option<u32>,
+    /// Information elements of the reassociation request
+    pub request_ies: Vec<u8>,
+    /// Information elements of the reassociation response
+    pub response_ies: Vec<u8>,
+    /// The 4-way handshake was completed by the driver
+    pub port_authorized: bool,
+}
+
+impl fmt::Display for RoamEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Roamed")?;
+        if let Some(ref bssid) = self.bssid {
+            write!(f, " to {}", bssid)?;
+        }
+        if let Some(frequency) = self.frequency {
+            write!(f, " on {} MHz", frequency)?;
+        }
+        Ok(())
+    }
+}
+
+/// The connection was terminated
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisconnectEvent {
+    /// Reason code of the deauthentication or disassociation
+    pub reason: Option<ReasonCode>,
+    /// The disconnect was initiated by the AP
+    pub by_ap: bool,
+    /// Information elements of the deauthentication or disassociation frame
+    pub ies: Vec<u8>,
+}
+
+impl fmt::Display for DisconnectEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Disconnected")?;
+        if self.by_ap {
+            write!(f, " by AP")?;
+        }
+        if let Some(reason) = self.reason {
+            write!(f, ": {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Connection state notification
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConnectionEvent {
+    Connect(ConnectResult),
+    Roam(RoamEvent),
+    Disconnect(DisconnectEvent),
+}
+
+impl ConnectionEvent {
+    /// Parse a connection event, `None` if the message is some other command
+    pub fn from_message(message: &generic::Message) -> Option<ConnectionEvent> {
+        let command = Command::convert_from(message.command)?;
+        let mut bssid = None;
+        let mut status = None;
+        let mut timed_out = false;
+        let mut timeout_reason = None;
+        let mut request_ies = vec![];
+        let mut response_ies = vec![];
+        let mut frequency = None;
+        let mut port_authorized = false;
+        let mut reason = None;
+        let mut by_ap = false;
+        let mut ies = vec![];
+        for attr in &message.attributes {
+            match Attribute::convert_from(attr.identifier) {
+                Some(Attribute::Mac) => {
+                    bssid = attr.as_hardware_address().ok();
+                }
+                Some(Attribute::StatusCode) => {
+                    status = attr.as_u16().ok().map(StatusCode::from);
+                }
+                Some(Attribute::TimedOut) => {
+                    timed_out = true;
+                }
+                Some(Attribute::TimeoutReason) => {
+                    timeout_reason = attr.as_u32().ok().and_then(TimeoutReason::convert_from);
+                }
+                Some(Attribute::ReqIe) => {
+                    request_ies = attr.as_bytes();
+                }
+                Some(Attribute::RespIe) => {
+                    response_ies = attr.as_bytes();
+                }
+                Some(Attribute::WiphyFreq) => {
+                    frequency = attr.as_u32().ok();
+                }
+                Some(Attribute::PortAuthorized) => {
+                    port_authorized = true;
+                }
+                Some(Attribute::ReasonCode) => {
+                    reason = attr.as_u16().ok().map(ReasonCode::from);
+                }
+                Some(Attribute::DisconnectedByAp) => {
+                    by_ap = true;
+                }
+                Some(Attribute::InformationElement) => {
+                    ies = attr.as_bytes();
+                }
+                _ => (),
+            }
+        }
+        match command {
+            Command::Connect => Some(ConnectionEvent::Connect(ConnectResult {
+                bssid,
+                status: status.unwrap_or(StatusCode::UnspecifiedFailure),
+                timed_out,
+                timeout_reason,
+                request_ies,
+                response_ies,
+                frequency,
+                port_authorized,
+            })),
+            Command::Roam => Some(ConnectionEvent::Roam(RoamEvent {
+                bssid,
+                frequency,
+                request_ies,
+                response_ies,
+                port_authorized,
+            })),
+            Command::Disconnect => Some(ConnectionEvent::Disconnect(DisconnectEvent {
+                reason,
+                by_ap,
+                ies,
+            })),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConnectionEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConnectionEvent::Connect(ref result) => result.fmt(f),
+            ConnectionEvent::Roam(ref event) => event.fmt(f),
+            ConnectionEvent::Disconnect(ref event) => event.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! ## IBSS
+//!
+//! Parameters for joining an independent BSS, an ad-hoc network without an access point.
+
+use std::io;
+
+use crate::attributes::{Attribute, ChannelWidth};
+use crate::chandef::Chandef;
+use crate::connect::ControlPortFlags;
+use crate::pack::flag_attribute;
+use crate::wireless_phy::{ExtendedFeaturesFlags, FeatureFlags, InterfaceTypeFlags, WirelessPhy};
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{Error, HardwareAddress};
+
+const MAX_SSID_LENGTH: usize = 32;
+const MAX_BASIC_RATES: usize = 32;
+const MAX_BEACON_INTERVAL: u16 = 10000;
+const HT_CAPABILITY_LENGTH: usize = 26;
+
+/// IBSS join parameters
+///
+/// An existing IBSS with the SSID is joined, otherwise a new one is created on the channel.
+#[derive(Clone)]
+pub struct IbssParams {
+    ssid: Vec<u8>,
+    chandef: Chandef,
+    fixed_frequency: bool,
+    bssid: Option<HardwareAddress>,
+    beacon_interval: Option<u16>,
+    basic_rates: Option<Vec<u8>>,
+    multicast_rate: Option<u32>,
+    privacy: bool,
+    control_port: Option<ControlPortFlags>,
+    ht_capability: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl IbssParams {
+    /// Join or create the IBSS with the SSID on the channel
+    pub fn new(ssid: &[u8], chandef: Chandef) -> IbssParams {
+        IbssParams {
+            ssid: ssid.to_vec(),
+            chandef,
+            fixed_frequency: false,
+            bssid: None,
+            beacon_interval: None,
+            basic_rates: None,
+            multicast_rate: None,
+            privacy: false,
+            control_port: None,
+            ht_capability: None,
+        }
+    }
+
+    /// Only join an IBSS on the channel, do not search other channels
+    pub fn fixed_frequency(mut self) -> IbssParams {
+        self.fixed_frequency = true;
+        self
+    }
+
+    /// Only join the IBSS with the given BSSID
+    pub fn bssid(mut self, bssid: HardwareAddress) -> IbssParams {
+        self.bssid = Some(bssid);
+        self
+    }
+
+    /// Beacon interval in TU, used when creating the IBSS
+    pub fn beacon_interval(mut self, interval: u16) -> IbssParams {
+        self.beacon_interval = Some(interval);
+        self
+    }
+
+    /// Basic rates in units of 500 kbit/s, used when creating the IBSS
+    pub fn basic_rates(mut self, rates: &[u8]) -> IbssParams {
+        self.basic_rates = Some(rates.to_vec());
+        self
+    }
+
+    /// Rate for multicast frames in units of 100 kbit/s
+    pub fn multicast_rate(mut self, rate: u32) -> IbssParams {
+        self.multicast_rate = Some(rate);
+        self
+    }
+
+    /// Set the privacy bit in the beacons, required for an encrypted IBSS
+    pub fn privacy(mut self) -> IbssParams {
+        self.privacy = true;
+        self
+    }
+
+    /// Only let control port frames through until the peers are authorized
+    pub fn control_port(mut self, flags: ControlPortFlags) -> IbssParams {
+        self.control_port = Some(flags);
+        self
+    }
+
+    /// Override the HT capabilities, only the bits set in `mask` are taken from `capability`
+    pub fn ht_capability(mut self, capability: &[u8], mask: &[u8]) -> IbssParams {
+        self.ht_capability = Some((capability.to_vec(), mask.to_vec()));
+        self
+    }
+
+    /// Check the parameters against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if !phy.interface_types().contains(InterfaceTypeFlags::ADHOC) {
+            return invalid("IBSS not supported");
+        }
+        if self.ssid.is_empty() || self.ssid.len() > MAX_SSID_LENGTH {
+            return invalid("Invalid SSID");
+        }
+        self.chandef.validate()?;
+        let width_supported = match self.chandef.width {
+            ChannelWidth::NoHt20 | ChannelWidth::Width5 | ChannelWidth::Width10 => true,
+            ChannelWidth::Width20 | ChannelWidth::Width40 => {
+                phy.features().contains(FeatureFlags::HT_IBSS)
+            }
+            ChannelWidth::Width80 | ChannelWidth::Width80P80 | ChannelWidth::Width160 => {
+                phy.features().contains(FeatureFlags::HT_IBSS)
+                    && phy
+                        .extended_features()
+                        .contains(ExtendedFeaturesFlags::VHT_IBSS)
+            }
+            ChannelWidth::Width320
+            | ChannelWidth::Width1
+            | ChannelWidth::Width2
+            | ChannelWidth::Width4
+            | ChannelWidth::Width8
+            | ChannelWidth::Width16 => false,
+        };
+        if !width_supported {
+            return invalid("Channel width not supported in an IBSS");
+        }
+        if let Some(interval) = self.beacon_interval {
+            if interval == 0 || interval > MAX_BEACON_INTERVAL {
+                return invalid("Invalid beacon interval");
+            }
+        }
+        if let Some(ref rates) = self.basic_rates {
+            if rates.is_empty() || rates.len() > MAX_BASIC_RATES {
+                return invalid("Invalid basic rates");
+            }
+        }
+        if self.multicast_rate == Some(0) {
+            return invalid("Invalid multicast rate");
+        }
+        if let Some(flags) = self.control_port {
+            if flags.intersects(ControlPortFlags::NO_ENCRYPT | ControlPortFlags::NO_PREAUTH) {
+                return invalid("Control port flags not supported in an IBSS");
+            }
+            if flags.contains(ControlPortFlags::OVER_NL80211)
+                && !phy
+                    .extended_features()
+                    .contains(ExtendedFeaturesFlags::CONTROL_PORT_OVER_NL80211)
+            {
+                return invalid("Control port over nl80211 not supported");
+            }
+        }
+        if let Some((ref capability, ref mask)) = self.ht_capability {
+            if capability.len() != HT_CAPABILITY_LENGTH || mask.len() != HT_CAPABILITY_LENGTH {
+                return invalid("Invalid HT capability length");
+            }
+        }
+        Ok(())
+    }
+
+    /// Append the parameters to a join IBSS message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        message.append_attribute(netlink::Attribute::new_bytes(Attribute::Ssid, &self.ssid));
+        self.chandef.append_attributes(message);
+        if self.fixed_frequency {
+            message.append_attribute(flag_attribute(Attribute::FreqFixed));
+        }
+        if let Some(bssid) = self.bssid {
+            message.append_attribute(netlink::Attribute::new(Attribute::Mac, bssid));
+        }
+        if let Some(interval) = self.beacon_interval {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::BeaconInterval,
+                u32::from(interval),
+            ));
+        }
+        if let Some(ref rates) = self.basic_rates {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::BssBasicRates,
+                rates,
+            ));
+        }
+        if let Some(rate) = self.multicast_rate {
+            message.append_attribute(netlink::Attribute::new(Attribute::McastRate, rate));
+        }
+        if self.privacy {
+            message.append_attribute(flag_attribute(Attribute::Privacy));
+        }
+        if let Some(flags) = self.control_port {
+            message.append_attribute(flag_attribute(Attribute::ControlPort));
+            if flags.contains(ControlPortFlags::OVER_NL80211) {
+                message.append_attribute(flag_attribute(Attribute::ControlPortFrameOverNl80211));
+            }
+        }
+        if let Some((ref capability, ref mask)) = self.ht_capability {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::HtCapability,
+                capability,
+            ));
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::HtCapabilityMask,
+                mask,
+            ));
+        }
+    }
+}
@@ -8,27 +8,71 @@ extern crate byteorder;
 #[macro_use]
 extern crate bitflags;
 extern crate encoding;
+extern crate libc;
 extern crate netlink_rust;
 
+mod ap;
 mod attributes;
+mod bss;
+mod chandef;
 mod commands;
+mod connect;
 mod frame;
+mod ibss;
 pub mod information_element;
 mod information_element_ids;
+mod mesh;
+mod mlme;
+mod pack;
 mod regulatory;
+mod scan;
+mod station;
+mod status;
+mod survey;
 mod unpack;
 mod wireless_interface;
 mod wireless_phy;
 
-pub use crate::attributes::{Attribute, BssAttribute, InterfaceType};
+pub use crate::ap::{BeaconConfig, BssParameters};
+pub use crate::attributes::{
+    AccessCategory, Attribute, AuthenticationType, Band, BssAttribute, ChannelWidth,
+    EhtGuardInterval, EhtResourceUnitAllocation, HeGuardInterval, HeResourceUnitAllocation,
+    HiddenSsid, InterfaceType, ManagementFrameProtection, MeshPowerMode, PeerLinkState,
+    StationInformationAttributes, TimeoutReason,
+};
+pub use crate::bss::{Bss, BssStatus};
+pub use crate::chandef::{Chandef, ChannelSwitchEvent};
 pub use crate::commands::Command;
+pub use crate::connect::{
+    BssSelect, ConnectParams, ConnectResult, ConnectionEvent, ControlPortFlags, DisconnectEvent,
+    RoamEvent, WpaVersions,
+};
 pub use crate::frame::Frame;
+pub use crate::ibss::IbssParams;
 pub use crate::information_element_ids::InformationElementId;
+pub use crate::mesh::{
+    HwmpRootMode, MeshAuthProtocol, MeshConfig, MeshPath, MeshPathFlags, MeshSetup,
+};
+pub use crate::mlme::{AssociateParams, AuthenticateParams, MlmeEvent, MlmeFrame};
 pub use crate::regulatory::{
     RegulatoryChange, RegulatoryInformation, RegulatoryInitiator, RegulatoryRegion,
 };
-pub use crate::wireless_interface::{get_wireless_interfaces, WirelessDeviceId, WirelessInterface};
-pub use crate::wireless_phy::get_wireless_phys;
+pub use crate::scan::{
+    MatchSet, ScanFlags, ScanPlan, ScanRequest, ScheduledScanEvent, ScheduledScanRequest,
+};
+pub use crate::station::{
+    BssParam, DisconnectFrame, RateInfo, RateWidth, StationFlagUpdate, StationFlags, StationInfo,
+    StationParams, TidStats, TxqStats,
+};
+pub use crate::status::{ReasonCode, StatusCode};
+pub use crate::survey::SurveyInfo;
+pub use crate::wireless_interface::{
+    get_wireless_interfaces, InterfaceOptions, MonitorFlags, WirelessDeviceId, WirelessInterface,
+};
+pub use crate::wireless_phy::{
+    get_wireless_phys, ExtendedFeaturesFlags, FeatureFlags, InterfaceTypeFlags, TxPower,
+    WiphyTxqParams, WirelessPhy,
+};
 
 fn join_to_string<T>(values: T, separator: &str) -> String
 where
@@ -0,0 +1,830 @@
+//! ## Mesh
+//!
+//! Joining an 802.11s mesh, the configuration of a mesh point and its mesh path tables.
+
+use std::fmt;
+use std::io;
+
+use crate::attributes::{
+    Attribute, MeshConfigAttribute, MeshPathInfoAttribute, MeshPowerMode, MeshSetupAttribute,
+};
+use crate::chandef::Chandef;
+use crate::commands::Command;
+use crate::pack::{flag_attribute, nested_attribute};
+use crate::wireless_phy::{FeatureFlags, InterfaceTypeFlags, WirelessPhy};
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{ConvertFrom, Error, HardwareAddress};
+
+const MAX_MESH_ID_LENGTH: usize = 32;
+const MAX_BASIC_RATES: usize = 32;
+const MAX_BEACON_INTERVAL: u16 = 10000;
+const MAX_DTIM_PERIOD: u8 = 100;
+const MAX_PEER_LINKS: u16 = 255;
+const MAX_RETRIES: u8 = 16;
+/// Authentication protocol identifiers
+const AUTH_PROTOCOL_SAE: u8 = 1;
+const AUTH_PROTOCOL_VENDOR: u8 = 255;
+
+/// HWMP root mode of a mesh point
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HwmpRootMode {
+    /// Not a root mesh point
+    NoRoot,
+    /// Root mesh point without proactive path selection
+    Root,
+    /// Root sending proactive PREQs without requesting PREPs
+    ProactivePreqWithoutPrep,
+    /// Root sending proactive PREQs, mesh points reply with PREPs
+    ProactivePreqWithPrep,
+    /// Root sending root announcements
+    RootAnnouncement,
+}
+
+impl HwmpRootMode {
+    fn from_u8(value: u8) -> Option<HwmpRootMode> {
+        match value {
+            0 => Some(HwmpRootMode::NoRoot),
+            1 => Some(HwmpRootMode::Root),
+            2 => Some(HwmpRootMode::ProactivePreqWithoutPrep),
+            3 => Some(HwmpRootMode::ProactivePreqWithPrep),
+            4 => Some(HwmpRootMode::RootAnnouncement),
+            _ => None,
+        }
+    }
+}
+
+impl From<HwmpRootMode> for u8 {
+    fn from(mode: HwmpRootMode) -> u8 {
+        match mode {
+            HwmpRootMode::NoRoot => 0,
+            HwmpRootMode::Root => 1,
+            HwmpRootMode::ProactivePreqWithoutPrep => 2,
+            HwmpRootMode::ProactivePreqWithPrep => 3,
+            HwmpRootMode::RootAnnouncement => 4,
+        }
+    }
+}
+
+/// Mesh configuration
+///
+/// Read with `WirelessInterface::get_mesh_config`, only the parameters which are set are
+/// changed by `WirelessInterface::set_mesh_config`. Timeouts and intervals are in TU unless
+/// noted otherwise.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshConfig {
+    /// Initial retry timeout of peer link establishment
+    pub retry_timeout: Option<u16>,
+    /// Confirm timeout of peer link establishment
+    pub confirm_timeout: Option<u16>,
+    /// Holding timeout of peer link establishment
+    pub holding_timeout: Option<u16>,
+    /// Maximum number of peer links
+    pub max_peer_links: Option<u16>,
+    /// Maximum number of peer link open retries
+    pub max_retries: Option<u8>,
+    /// Time to live of mesh frames
+    pub ttl: Option<u8>,
+    /// Time to live of path selection elements
+    pub element_ttl: Option<u8>,
+    /// Open peer links automatically with compatible mesh points
+    pub auto_open_plinks: Option<bool>,
+    /// Interval in ms between refreshes of active paths
+    pub path_refresh_time: Option<u32>,
+    /// Minimum path discovery timeout in ms
+    pub min_discovery_timeout: Option<u16>,
+    /// Time a discovered path stays valid
+    pub hwmp_active_path_timeout: Option<u32>,
+    /// Minimum interval between PREQs
+    pub hwmp_preq_min_interval: Option<u16>,
+    /// Minimum interval between PERRs
+    pub hwmp_perr_min_interval: Option<u16>,
+    /// Time for a frame to traverse the mesh
+    pub hwmp_net_diameter_traversal_time: Option<u16>,
+    pub hwmp_root_mode: Option<HwmpRootMode>,
+    /// Interval between root announcements
+    pub hwmp_rann_interval: Option<u16>,
+    /// Interval between proactive PREQs of a root
+    pub hwmp_root_interval: Option<u16>,
+    /// Minimum interval between PREQs to the root
+    pub hwmp_confirmation_interval: Option<u16>,
+    /// Time a path to the root stays valid
+    pub hwmp_path_to_root_timeout: Option<u32>,
+    /// Announce the mesh point as a gate to another network
+    pub gate_announcements: Option<bool>,
+    /// Forward frames of other mesh points
+    pub forwarding: Option<bool>,
+    /// Minimum signal strength in dBm of peers, zero disables the threshold
+    pub rssi_threshold: Option<i32>,
+    /// Default power mode of new peer links
+    pub power_mode: Option<MeshPowerMode>,
+    /// Awake window duration after DTIM beacons in TU
+    pub awake_window: Option<u16>,
+    /// Inactivity time in seconds before a peer link is closed, zero disables the timeout
+    pub plink_timeout: Option<u32>,
+    /// Advertise that the mesh point is connected to a gate
+    pub connected_to_gate: Option<bool>,
+}
+
+impl MeshConfig {
+    /// Parse the mesh configuration from the nested mesh configuration attributes
+    pub fn from_attributes(attributes: &[netlink::Attribute]) -> Result<MeshConfig, Error> {
+        let mut config = MeshConfig::default();
+        for attr in attributes {
+            let identifier = match MeshConfigAttribute::convert_from(attr.identifier) {
+                Some(identifier) => identifier,
+                None => continue,
+            };
+            match identifier {
+                MeshConfigAttribute::RetryTimeout => {
+                    config.retry_timeout = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::ConfirmTimeout => {
+                    config.confirm_timeout = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::HoldingTimeout => {
+                    config.holding_timeout = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::MaxPeerLinks => {
+                    config.max_peer_links = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::MaxRetries => {
+                    config.max_retries = Some(attr.as_u8()?);
+                }
+                MeshConfigAttribute::Ttl => {
+                    config.ttl = Some(attr.as_u8()?);
+                }
+                MeshConfigAttribute::ElementTtl => {
+                    config.element_ttl = Some(attr.as_u8()?);
+                }
+                MeshConfigAttribute::AutoOpenPlinks => {
+                    config.auto_open_plinks = Some(attr.as_u8()? != 0);
+                }
+                MeshConfigAttribute::PathRefreshTime => {
+                    config.path_refresh_time = Some(attr.as_u32()?);
+                }
+                MeshConfigAttribute::MinDiscoveryTimeout => {
+                    config.min_discovery_timeout = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::HwmpActivePathTimeout => {
+                    config.hwmp_active_path_timeout = Some(attr.as_u32()?);
+                }
+                MeshConfigAttribute::HwmpPreqMinInterval => {
+                    config.hwmp_preq_min_interval = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::HwmpPerrMinInterval => {
+                    config.hwmp_perr_min_interval = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::HwmpNetDiameterTraversalTime => {
+                    config.hwmp_net_diameter_traversal_time = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::HwmpRootMode => {
+                    config.hwmp_root_mode = HwmpRootMode::from_u8(attr.as_u8()?);
+                }
+                MeshConfigAttribute::HwmpRannInterval => {
+                    config.hwmp_rann_interval = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::HwmpRootInterval => {
+                    config.hwmp_root_interval = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::HwmpConfirmationInterval => {
+                    config.hwmp_confirmation_interval = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::HwmpPathToRootTimeout => {
+                    config.hwmp_path_to_root_timeout = Some(attr.as_u32()?);
+                }
+                MeshConfigAttribute::GateAnnouncements => {
+                    config.gate_announcements = Some(attr.as_u8()? != 0);
+                }
+                MeshConfigAttribute::Forwarding => {
+                    config.forwarding = Some(attr.as_u8()? != 0);
+                }
+                MeshConfigAttribute::RssiThreshold => {
+                    config.rssi_threshold = Some(attr.as_i32()?);
+                }
+                MeshConfigAttribute::PowerMode => {
+                    config.power_mode = MeshPowerMode::convert_from(attr.as_u32()?);
+                }
+                MeshConfigAttribute::AwakeWindow => {
+                    config.awake_window = Some(attr.as_u16()?);
+                }
+                MeshConfigAttribute::PlinkTimeout => {
+                    config.plink_timeout = Some(attr.as_u32()?);
+                }
+                MeshConfigAttribute::ConnectedToGate => {
+                    config.connected_to_gate = Some(attr.as_u8()? != 0);
+                }
+                _ => (),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Parse the mesh configuration from a get mesh configuration reply
+    pub fn from_message(message: &generic::Message) -> Result<MeshConfig, Error> {
+        if message.command != Command::GetMeshConfig {
+            return Err(
+                io::Error::new(io::ErrorKind::InvalidData, "Not a mesh configuration").into(),
+            );
+        }
+        for attr in &message.attributes {
+            if Attribute::convert_from(attr.identifier) == Some(Attribute::MeshConfig) {
+                let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                return MeshConfig::from_attributes(&attrs);
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::NotFound, "Mesh configuration not found").into())
+    }
+
+    /// Check the ranges of the parameters which are set
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if matches!(self.max_peer_links, Some(links) if links > MAX_PEER_LINKS) {
+            return invalid("Invalid maximum number of peer links");
+        }
+        if matches!(self.max_retries, Some(retries) if retries > MAX_RETRIES) {
+            return invalid("Invalid maximum number of retries");
+        }
+        if self.ttl == Some(0) || self.element_ttl == Some(0) {
+            return invalid("Invalid time to live");
+        }
+        if self.hwmp_preq_min_interval == Some(0) || self.hwmp_perr_min_interval == Some(0) {
+            return invalid("Invalid HWMP interval");
+        }
+        if self.hwmp_rann_interval == Some(0) || self.hwmp_root_interval == Some(0) {
+            return invalid("Invalid HWMP interval");
+        }
+        if matches!(self.rssi_threshold, Some(threshold) if !(-255..=0).contains(&threshold)) {
+            return invalid("Invalid RSSI threshold");
+        }
+        if self.power_mode == Some(MeshPowerMode::Unknown) {
+            return invalid("Invalid power mode");
+        }
+        Ok(())
+    }
+
+    /// Append the parameters which are set as a nested mesh configuration attribute
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        let mut attrs = vec![];
+        if let Some(timeout) = self.retry_timeout {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::RetryTimeout,
+                timeout,
+            ));
+        }
+        if let Some(timeout) = self.confirm_timeout {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::ConfirmTimeout,
+                timeout,
+            ));
+        }
+        if let Some(timeout) = self.holding_timeout {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HoldingTimeout,
+                timeout,
+            ));
+        }
+        if let Some(links) = self.max_peer_links {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::MaxPeerLinks,
+                links,
+            ));
+        }
+        if let Some(retries) = self.max_retries {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::MaxRetries,
+                retries,
+            ));
+        }
+        if let Some(ttl) = self.ttl {
+            attrs.push(netlink::Attribute::new(MeshConfigAttribute::Ttl, ttl));
+        }
+        if let Some(ttl) = self.element_ttl {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::ElementTtl,
+                ttl,
+            ));
+        }
+        if let Some(enable) = self.auto_open_plinks {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::AutoOpenPlinks,
+                enable as u8,
+            ));
+        }
+        if let Some(time) = self.path_refresh_time {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::PathRefreshTime,
+                time,
+            ));
+        }
+        if let Some(timeout) = self.min_discovery_timeout {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::MinDiscoveryTimeout,
+                timeout,
+            ));
+        }
+        if let Some(timeout) = self.hwmp_active_path_timeout {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HwmpActivePathTimeout,
+                timeout,
+            ));
+        }
+        if let Some(interval) = self.hwmp_preq_min_interval {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HwmpPreqMinInterval,
+                interval,
+            ));
+        }
+        if let Some(interval) = self.hwmp_perr_min_interval {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HwmpPerrMinInterval,
+                interval,
+            ));
+        }
+        if let Some(time) = self.hwmp_net_diameter_traversal_time {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HwmpNetDiameterTraversalTime,
+                time,
+            ));
+        }
+        if let Some(mode) = self.hwmp_root_mode {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HwmpRootMode,
+                u8::from(mode),
+            ));
+        }
+        if let Some(interval) = self.hwmp_rann_interval {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HwmpRannInterval,
+                interval,
+            ));
+        }
+        if let Some(interval) = self.hwmp_root_interval {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HwmpRootInterval,
+                interval,
+            ));
+        }
+        if let Some(interval) = self.hwmp_confirmation_interval {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HwmpConfirmationInterval,
+                interval,
+            ));
+        }
+        if let Some(timeout) = self.hwmp_path_to_root_timeout {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::HwmpPathToRootTimeout,
+                timeout,
+            ));
+        }
+        if let Some(enable) = self.gate_announcements {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::GateAnnouncements,
+                enable as u8,
+            ));
+        }
+        if let Some(enable) = self.forwarding {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::Forwarding,
+                enable as u8,
+            ));
+        }
+        if let Some(threshold) = self.rssi_threshold {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::RssiThreshold,
+                threshold,
+            ));
+        }
+        if let Some(ref mode) = self.power_mode {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::PowerMode,
+                u32::from(mode.clone()),
+            ));
+        }
+        if let Some(window) = self.awake_window {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::AwakeWindow,
+                window,
+            ));
+        }
+        if let Some(timeout) = self.plink_timeout {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::PlinkTimeout,
+                timeout,
+            ));
+        }
+        if let Some(connected) = self.connected_to_gate {
+            attrs.push(netlink::Attribute::new(
+                MeshConfigAttribute::ConnectedToGate,
+                connected as u8,
+            ));
+        }
+        message.append_attribute(nested_attribute(Attribute::MeshConfig, &attrs));
+    }
+}
+
+/// Protocol used for authenticating mesh peers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MeshAuthProtocol {
+    /// Simultaneous authentication of equals
+    Sae,
+    /// Vendor specific protocol
+    Vendor,
+}
+
+/// Parameters for joining a mesh
+///
+/// The mesh uses HWMP path selection and the airtime link metric unless the vendor specific
+/// protocols are enabled.
+#[derive(Clone)]
+pub struct MeshSetup {
+    mesh_id: Vec<u8>,
+    chandef: Option<Chandef>,
+    config: Option<MeshConfig>,
+    vendor_path_selection: bool,
+    vendor_metric: bool,
+    auth_protocol: Option<MeshAuthProtocol>,
+    userspace_ampe: bool,
+    user_mpm: bool,
+    information_elements: Vec<u8>,
+    beacon_interval: Option<u16>,
+    dtim_period: Option<u8>,
+    basic_rates: Option<Vec<u8>>,
+}
+
+impl MeshSetup {
+    /// Join the mesh with the given mesh ID
+    pub fn new(mesh_id: &[u8]) -> MeshSetup {
+        MeshSetup {
+            mesh_id: mesh_id.to_vec(),
+            chandef: None,
+            config: None,
+            vendor_path_selection: false,
+            vendor_metric: false,
+            auth_protocol: None,
+            userspace_ampe: false,
+            user_mpm: false,
+            information_elements: vec![],
+            beacon_interval: None,
+            dtim_period: None,
+            basic_rates: None,
+        }
+    }
+
+    /// Channel of the mesh, the current channel of the interface is used otherwise
+    pub fn chandef(mut self, chandef: Chandef) -> MeshSetup {
+        self.chandef = Some(chandef);
+        self
+    }
+
+    /// Mesh configuration to join with
+    pub fn config(mut self, config: MeshConfig) -> MeshSetup {
+        self.config = Some(config);
+        self
+    }
+
+    /// Use a vendor specific path selection protocol instead of HWMP
+    pub fn vendor_path_selection(mut self) -> MeshSetup {
+        self.vendor_path_selection = true;
+        self
+    }
+
+    /// Use a vendor specific path metric instead of the airtime link metric
+    pub fn vendor_metric(mut self) -> MeshSetup {
+        self.vendor_metric = true;
+        self
+    }
+
+    /// Peers are authenticated by a userspace daemon with the given protocol
+    ///
+    /// Requires userspace mesh peering management, which userspace AMPE implies.
+    pub fn auth_protocol(mut self, protocol: MeshAuthProtocol) -> MeshSetup {
+        self.auth_protocol = Some(protocol);
+        self
+    }
+
+    /// Authenticated mesh peering exchange (AMPE) is handled in userspace
+    pub fn userspace_ampe(mut self) -> MeshSetup {
+        self.userspace_ampe = true;
+        self
+    }
+
+    /// The mesh peering management (MPM) is handled in userspace
+    pub fn user_mpm(mut self) -> MeshSetup {
+        self.user_mpm = true;
+        self
+    }
+
+    /// Vendor specific information elements to add to beacons and peering frames
+    pub fn information_elements(mut self, data: &[u8]) -> MeshSetup {
+        self.information_elements = data.to_vec();
+        self
+    }
+
+    /// Beacon interval in TU
+    pub fn beacon_interval(mut self, interval: u16) -> MeshSetup {
+        self.beacon_interval = Some(interval);
+        self
+    }
+
+    /// Number of beacon intervals between DTIMs
+    pub fn dtim_period(mut self, period: u8) -> MeshSetup {
+        self.dtim_period = Some(period);
+        self
+    }
+
+    /// Basic rates in units of 500 kbit/s
+    pub fn basic_rates(mut self, rates: &[u8]) -> MeshSetup {
+        self.basic_rates = Some(rates.to_vec());
+        self
+    }
+
+    /// Check the setup against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if !phy
+            .interface_types()
+            .contains(InterfaceTypeFlags::MESHPOINT)
+        {
+            return invalid("Mesh point not supported");
+        }
+        if self.mesh_id.is_empty() || self.mesh_id.len() > MAX_MESH_ID_LENGTH {
+            return invalid("Invalid mesh ID");
+        }
+        let user_mpm = self.user_mpm || self.userspace_ampe;
+        if user_mpm && !phy.features().contains(FeatureFlags::USERSPACE_MPM) {
+            return invalid("Userspace mesh peering management not supported");
+        }
+        if self.auth_protocol.is_some() && !user_mpm {
+            return invalid("Authentication protocol requires userspace mesh peering management");
+        }
+        if let Some(interval) = self.beacon_interval {
+            if interval == 0 || interval > MAX_BEACON_INTERVAL {
+                return invalid("Invalid beacon interval");
+            }
+        }
+        if let Some(period) = self.dtim_period {
+            if period == 0 || period > MAX_DTIM_PERIOD {
+                return invalid("Invalid DTIM period");
+            }
+        }
+        if let Some(ref rates) = self.basic_rates {
+            if rates.is_empty() || rates.len() > MAX_BASIC_RATES {
+                return invalid("Invalid basic rates");
+            }
+        }
+        if let Some(ref chandef) = self.chandef {
+            chandef.validate()?;
+        }
+        if let Some(ref config) = self.config {
+            config.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Append the setup to a join mesh message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        message.append_attribute(netlink::Attribute::new_bytes(
+            Attribute::MeshId,
+            &self.mesh_id,
+        ));
+        if let Some(ref chandef) = self.chandef {
+            chandef.append_attributes(message);
+        }
+        if let Some(interval) = self.beacon_interval {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::BeaconInterval,
+                u32::from(interval),
+            ));
+        }
+        if let Some(period) = self.dtim_period {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::DtimPeriod,
+                u32::from(period),
+            ));
+        }
+        if let Some(ref rates) = self.basic_rates {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::BssBasicRates,
+                rates,
+            ));
+        }
+        if let Some(ref config) = self.config {
+            config.append_attributes(message);
+        }
+        let mut attrs = vec![
+            netlink::Attribute::new(
+                MeshSetupAttribute::EnableVendorPathSelection,
+                self.vendor_path_selection as u8,
+            ),
+            netlink::Attribute::new(
+                MeshSetupAttribute::EnableVendorMetric,
+                self.vendor_metric as u8,
+            ),
+        ];
+        if !self.information_elements.is_empty() {
+            attrs.push(netlink::Attribute::new_bytes(
+                MeshSetupAttribute::InformationElement,
+                &self.information_elements,
+            ));
+        }
+        if let Some(protocol) = self.auth_protocol {
+            attrs.push(flag_attribute(MeshSetupAttribute::UserspaceAuth));
+            let protocol = match protocol {
+                MeshAuthProtocol::Sae => AUTH_PROTOCOL_SAE,
+                MeshAuthProtocol::Vendor => AUTH_PROTOCOL_VENDOR,
+            };
+            attrs.push(netlink::Attribute::new(
+                MeshSetupAttribute::AuthProtocol,
+                protocol,
+            ));
+        }
+        if self.userspace_ampe {
+            attrs.push(flag_attribute(MeshSetupAttribute::UserspaceAmpe));
+        }
+        if self.user_mpm {
+            attrs.push(flag_attribute(MeshSetupAttribute::UserspaceMpm));
+        }
+        message.append_attribute(nested_attribute(Attribute::MeshSetup, &attrs));
+    }
+}
+
+bitflags! {
+    /// Mesh path flags
+    pub struct MeshPathFlags: u8 {
+        /// The path is active
+        const ACTIVE    = 1 << 0;
+        /// The path discovery is in progress
+        const RESOLVING = 1 << 1;
+        /// The sequence number is valid
+        const SN_VALID  = 1 << 2;
+        /// The path is fixed and not updated by path selection
+        const FIXED     = 1 << 3;
+        /// The path discovery has completed
+        const RESOLVED  = 1 << 4;
+    }
+}
+
+/// Mesh path, an entry of the HWMP forwarding table or of the proxy path table
+///
+/// For a proxy path the next hop is the mesh point proxying the destination.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshPath {
+    pub destination: HardwareAddress,
+    pub next_hop: HardwareAddress,
+    /// Number of frames queued for the destination
+    pub frame_queue_length: Option<u32>,
+    /// HWMP sequence number of the destination
+    pub sequence_number: Option<u32>,
+    /// Path metric
+    pub metric: Option<u32>,
+    /// Time in ms until the path expires
+    pub expiration_time: Option<u32>,
+    pub flags: MeshPathFlags,
+    /// Path discovery timeout in ms
+    pub discovery_timeout: Option<u32>,
+    /// Number of path discovery retries
+    pub discovery_retries: Option<u8>,
+    /// Number of hops to the destination
+    pub hop_count: Option<u8>,
+    /// Number of times the next hop of the path changed
+    pub path_change_count: Option<u32>,
+}
+
+impl MeshPath {
+    /// Parse the mesh path information from the nested mesh path attributes
+    pub fn from_attributes(
+        destination: HardwareAddress,
+        next_hop: HardwareAddress,
+        attributes: &[netlink::Attribute],
+    ) -> Result<MeshPath, Error> {
+        let mut path = MeshPath {
+            destination,
+            next_hop,
+            frame_queue_length: None,
+            sequence_number: None,
+            metric: None,
+            expiration_time: None,
+            flags: MeshPathFlags::empty(),
+            discovery_timeout: None,
+            discovery_retries: None,
+            hop_count: None,
+            path_change_count: None,
+        };
+        for attr in attributes {
+            let identifier = match MeshPathInfoAttribute::convert_from(attr.identifier) {
+                Some(identifier) => identifier,
+                None => continue,
+            };
+            match identifier {
+                MeshPathInfoAttribute::FrameQueueLength => {
+                    path.frame_queue_length = Some(attr.as_u32()?);
+                }
+                MeshPathInfoAttribute::SequenceNumber => {
+                    path.sequence_number = Some(attr.as_u32()?);
+                }
+                MeshPathInfoAttribute::Metric => {
+                    path.metric = Some(attr.as_u32()?);
+                }
+                MeshPathInfoAttribute::ExpirationTime => {
+                    path.expiration_time = Some(attr.as_u32()?);
+                }
+                MeshPathInfoAttribute::Flags => {
+                    path.flags = MeshPathFlags::from_bits_truncate(attr.as_u8()?);
+                }
+                MeshPathInfoAttribute::DiscoveryTimeout => {
+                    path.discovery_timeout = Some(attr.as_u32()?);
+                }
+                MeshPathInfoAttribute::DiscoveryRetries => {
+                    path.discovery_retries = Some(attr.as_u8()?);
+                }
+                MeshPathInfoAttribute::HopCount => {
+                    path.hop_count = Some(attr.as_u8()?);
+                }
+                MeshPathInfoAttribute::PathChange => {
+                    path.path_change_count = Some(attr.as_u32()?);
+                }
+                _ => (),
+            }
+        }
+        Ok(path)
+    }
+
+    /// Parse the mesh path from a new mesh path message
+    pub fn from_message(message: &generic::Message) -> Result<MeshPath, Error> {
+        let mut destination = None;
+        let mut next_hop = None;
+        let mut attrs = None;
+        for attr in &message.attributes {
+            match Attribute::convert_from(attr.identifier) {
+                Some(Attribute::Mac) => {
+                    destination = Some(attr.as_hardware_address()?);
+                }
+                Some(Attribute::MpathNextHop) => {
+                    next_hop = Some(attr.as_hardware_address()?);
+                }
+                Some(Attribute::MpathInfo) => {
+                    let (_, path_attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                    attrs = Some(path_attrs);
+                }
+                _ => (),
+            }
+        }
+        match (destination, next_hop, attrs) {
+            (Some(destination), Some(next_hop), Some(attrs)) => {
+                MeshPath::from_attributes(destination, next_hop, &attrs)
+            }
+            _ => Err(io::Error::new(io::ErrorKind::NotFound, "Mesh Path Not Found").into()),
+        }
+    }
+}
+
+impl fmt::Display for MeshPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Destination: {} Next hop: {}",
+            self.destination, self.next_hop
+        )?;
+        if let Some(metric) = self.metric {
+            write!(f, " Metric: {}", metric)?;
+        }
+        if let Some(hop_count) = self.hop_count {
+            write!(f, " Hops: {}", hop_count)?;
+        }
+        if let Some(sequence_number) = self.sequence_number {
+            write!(f, " SN: {}", sequence_number)?;
+        }
+        if let Some(expiration_time) = self.expiration_time {
+            write!(f, " Expires: {} ms", expiration_time)?;
+        }
+        write!(f, " Flags: {:?}", self.flags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_mesh_config() {
+        assert!(MeshConfig::default().validate().is_ok());
+        let config = MeshConfig {
+            ttl: Some(31),
+            hwmp_root_mode: Some(HwmpRootMode::RootAnnouncement),
+            rssi_threshold: Some(-80),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        let config = MeshConfig {
+            rssi_threshold: Some(10),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        let config = MeshConfig {
+            power_mode: Some(MeshPowerMode::Unknown),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,529 @@
+//! ## MLME
+//!
+//! Parameters for the separate authentication and association steps, used when userspace does
+//! the station management instead of the kernel or the driver, and the MLME events reporting
+//! their outcome.
+
+use std::fmt;
+use std::io;
+
+use crate::attributes::{Attribute, AuthenticationType, ManagementFrameProtection};
+use crate::commands::Command;
+use crate::connect::{ControlPortFlags, CryptoSettings, WpaVersions};
+use crate::frame::{FrameControl, FrameSubtype};
+use crate::information_element::{AuthenticationKeyManagement, CipherSuite};
+use crate::pack::flag_attribute;
+use crate::status::{ReasonCode, StatusCode};
+use crate::unpack::LittleUnpack;
+use crate::wireless_phy::{ExtendedFeaturesFlags, FeatureFlags, WirelessPhy};
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{ConvertFrom, Error, HardwareAddress};
+
+const MAX_SSID_LENGTH: usize = 32;
+const HT_CAPABILITY_LENGTH: usize = 26;
+const VHT_CAPABILITY_LENGTH: usize = 12;
+const MANAGEMENT_HEADER_LENGTH: usize = 24;
+
+/// Authenticate request parameters
+#[derive(Clone)]
+pub struct AuthenticateParams {
+    bssid: HardwareAddress,
+    ssid: Vec<u8>,
+    frequency: u32,
+    auth_type: AuthenticationType,
+    auth_data: Option<Vec<u8>>,
+    information_elements: Vec<u8>,
+    local_state_change: bool,
+}
+
+impl AuthenticateParams {
+    /// Authenticate with the BSS on the frequency in MHz
+    pub fn new(
+        bssid: HardwareAddress,
+        ssid: &[u8],
+        frequency: u32,
+        auth_type: AuthenticationType,
+    ) -> AuthenticateParams {
+        AuthenticateParams {
+            bssid,
+            ssid: ssid.to_vec(),
+            frequency,
+            auth_type,
+            auth_data: None,
+            information_elements: vec![],
+            local_state_change: false,
+        }
+    }
+
+    /// Authentication frame body following the algorithm number, used with SAE and FILS
+    ///
+    /// For SAE this is the transaction sequence number and status code followed by the commit or
+    /// confirm message.
+    pub fn auth_data(mut self, data: &[u8]) -> AuthenticateParams {
+        self.auth_data = Some(data.to_vec());
+        self
+    }
+
+    /// Information elements to add to the authentication frame
+    pub fn information_elements(mut self, data: &[u8]) -> AuthenticateParams {
+        self.information_elements = data.to_vec();
+        self
+    }
+
+    /// Only update the local state, no authentication frame is sent
+    pub fn local_state_change(mut self) -> AuthenticateParams {
+        self.local_state_change = true;
+        self
+    }
+
+    /// Check the request against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if self.ssid.is_empty() || self.ssid.len() > MAX_SSID_LENGTH {
+            return invalid("Invalid SSID");
+        }
+        let is_fils = matches!(
+            self.auth_type,
+            AuthenticationType::FilsSharedKey
+                | AuthenticationType::FilsSharedKeyPfs
+                | AuthenticationType::FilsPublicKey
+        );
+        if self.auth_type == AuthenticationType::Sae && !phy.features().contains(FeatureFlags::SAE)
+        {
+            return invalid("SAE not supported");
+        }
+        if is_fils
+            && !phy
+                .extended_features()
+                .contains(ExtendedFeaturesFlags::BEACON_FILS_STA)
+        {
+            return invalid("FILS not supported");
+        }
+        if self.auth_data.is_some() && self.auth_type != AuthenticationType::Sae && !is_fils {
+            return invalid("Authentication data requires SAE or FILS");
+        }
+        Ok(())
+    }
+
+    /// Append the request parameters to an authenticate message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        message.append_attribute(netlink::Attribute::new(Attribute::Mac, self.bssid));
+        message.append_attribute(netlink::Attribute::new_bytes(Attribute::Ssid, &self.ssid));
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::WiphyFreq,
+            self.frequency,
+        ));
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::AuthType,
+            u32::from(self.auth_type.clone()),
+        ));
+        if let Some(ref data) = self.auth_data {
+            message.append_attribute(netlink::Attribute::new_bytes(Attribute::AuthData, data));
+        }
+        if !self.information_elements.is_empty() {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::InformationElement,
+                &self.information_elements,
+            ));
+        }
+        if self.local_state_change {
+            message.append_attribute(flag_attribute(Attribute::LocalStateChange));
+        }
+    }
+}
+
+/// Associate request parameters
+///
+/// Privacy is requested when WPA versions or ciphers are given.
+#[derive(Clone)]
+pub struct AssociateParams {
+    bssid: HardwareAddress,
+    ssid: Vec<u8>,
+    frequency: u32,
+    prev_bssid: Option<HardwareAddress>,
+    crypto: CryptoSettings,
+    mfp: Option<ManagementFrameProtection>,
+    information_elements: Vec<u8>,
+    ht_capability: Option<(Vec<u8>, Vec<u8>)>,
+    vht_capability: Option<(Vec<u8>, Vec<u8>)>,
+    disable_ht: bool,
+    disable_vht: bool,
+}
+
+impl AssociateParams {
+    /// Associate with an authenticated BSS on the frequency in MHz
+    pub fn new(bssid: HardwareAddress, ssid: &[u8], frequency: u32) -> AssociateParams {
+        AssociateParams {
+            bssid,
+            ssid: ssid.to_vec(),
+            frequency,
+            prev_bssid: None,
+            crypto: CryptoSettings::default(),
+            mfp: None,
+            information_elements: vec![],
+            ht_capability: None,
+            vht_capability: None,
+            disable_ht: false,
+            disable_vht: false,
+        }
+    }
+
+    /// Reassociate, moving the association from the BSS with the given BSSID
+    pub fn prev_bssid(mut self, bssid: HardwareAddress) -> AssociateParams {
+        self.prev_bssid = Some(bssid);
+        self
+    }
+
+    pub fn wpa_versions(mut self, versions: WpaVersions) -> AssociateParams {
+        self.crypto.wpa_versions = Some(versions);
+        self
+    }
+
+    /// Add a pairwise cipher suite
+    pub fn pairwise_cipher(mut self, cipher: CipherSuite) -> AssociateParams {
+        self.crypto.pairwise_ciphers.push(cipher);
+        self
+    }
+
+    pub fn group_cipher(mut self, cipher: CipherSuite) -> AssociateParams {
+        self.crypto.group_cipher = Some(cipher);
+        self
+    }
+
+    /// Add an authentication and key management suite
+    pub fn akm_suite(mut self, akm: AuthenticationKeyManagement) -> AssociateParams {
+        self.crypto.akm_suites.push(akm);
+        self
+    }
+
+    /// Use of management frame protection
+    pub fn mfp(mut self, mfp: ManagementFrameProtection) -> AssociateParams {
+        self.mfp = Some(mfp);
+        self
+    }
+
+    /// Information elements to add to the association request, this normally includes the RSN
+    /// element
+    pub fn information_elements(mut self, data: &[u8]) -> AssociateParams {
+        self.information_elements = data.to_vec();
+        self
+    }
+
+    /// Block data traffic until the control port is authorized
+    ///
+    /// `ethertype` defaults to EAPOL (0x888e).
+    pub fn control_port(
+        mut self,
+        ethertype: Option<u16>,
+        flags: ControlPortFlags,
+    ) -> AssociateParams {
+        self.crypto.control_port = Some((ethertype, flags));
+        self
+    }
+
+    /// Override the HT capabilities, only the bits set in `mask` are taken from `capability`
+    pub fn ht_capability(mut self, capability: &[u8], mask: &[u8]) -> AssociateParams {
+        self.ht_capability = Some((capability.to_vec(), mask.to_vec()));
+        self
+    }
+
+    /// Override the VHT capabilities, only the bits set in `mask` are taken from `capability`
+    pub fn vht_capability(mut self, capability: &[u8], mask: &[u8]) -> AssociateParams {
+        self.vht_capability = Some((capability.to_vec(), mask.to_vec()));
+        self
+    }
+
+    /// Associate without HT
+    pub fn disable_ht(mut self) -> AssociateParams {
+        self.disable_ht = true;
+        self
+    }
+
+    /// Associate without VHT
+    pub fn disable_vht(mut self) -> AssociateParams {
+        self.disable_vht = true;
+        self
+    }
+
+    /// Check the request against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if self.ssid.is_empty() || self.ssid.len() > MAX_SSID_LENGTH {
+            return invalid("Invalid SSID");
+        }
+        if let Some((ref capability, ref mask)) = self.ht_capability {
+            if capability.len() != HT_CAPABILITY_LENGTH || mask.len() != HT_CAPABILITY_LENGTH {
+                return invalid("Invalid HT capability length");
+            }
+        }
+        if let Some((ref capability, ref mask)) = self.vht_capability {
+            if capability.len() != VHT_CAPABILITY_LENGTH || mask.len() != VHT_CAPABILITY_LENGTH {
+                return invalid("Invalid VHT capability length");
+            }
+        }
+        if self.mfp == Some(ManagementFrameProtection::Optional)
+            && !phy
+                .extended_features()
+                .contains(ExtendedFeaturesFlags::MFP_OPTIONAL)
+        {
+            return invalid("Optional MFP not supported");
+        }
+        self.crypto.validate(phy)
+    }
+
+    /// Append the request parameters to an associate message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        message.append_attribute(netlink::Attribute::new(Attribute::Mac, self.bssid));
+        message.append_attribute(netlink::Attribute::new_bytes(Attribute::Ssid, &self.ssid));
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::WiphyFreq,
+            self.frequency,
+        ));
+        if let Some(bssid) = self.prev_bssid {
+            message.append_attribute(netlink::Attribute::new(Attribute::PrevBssid, bssid));
+        }
+        self.crypto.append_attributes(message);
+        if let Some(ref mfp) = self.mfp {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::UseMfp,
+                u32::from(mfp.clone()),
+            ));
+        }
+        if !self.information_elements.is_empty() {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::InformationElement,
+                &self.information_elements,
+            ));
+        }
+        if let Some((ref capability, ref mask)) = self.ht_capability {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::HtCapability,
+                capability,
+            ));
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::HtCapabilityMask,
+                mask,
+            ));
+        }
+        if let Some((ref capability, ref mask)) = self.vht_capability {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::VhtCapability,
+                capability,
+            ));
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::VhtCapabilityMask,
+                mask,
+            ));
+        }
+        if self.disable_ht {
+            message.append_attribute(flag_attribute(Attribute::DisableHt));
+        }
+        if self.disable_vht {
+            message.append_attribute(flag_attribute(Attribute::DisableVht));
+        }
+    }
+}
+
+/// Management frame reported with a MLME event
+#[derive(Clone, Debug, PartialEq)]
+pub struct MlmeFrame {
+    /// Transmitter of the frame
+    pub source: HardwareAddress,
+    /// BSSID of the frame
+    pub bssid: HardwareAddress,
+    /// Status code of authentication and association responses
+    pub status: Option<StatusCode>,
+    /// Reason code of deauthentication and disassociation frames
+    pub reason: Option<ReasonCode>,
+    /// Authentication algorithm and transaction sequence number of authentication frames
+    pub authentication: Option<(u16, u16)>,
+    /// The complete frame
+    pub data: Vec<u8>,
+}
+
+impl MlmeFrame {
+    /// Parse an authentication, association, deauthentication or disassociation frame
+    pub fn unpack(data: &[u8]) -> Result<MlmeFrame, Error> {
+        if data.len() < MANAGEMENT_HEADER_LENGTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid frame").into());
+        }
+        let control = FrameControl::from(u16::unpack_unchecked(data));
+        let source = HardwareAddress::unpack_unchecked(&data[10..]);
+        let bssid = HardwareAddress::unpack_unchecked(&data[16..]);
+        let offset = if control.get_order() {
+            MANAGEMENT_HEADER_LENGTH + 4
+        } else {
+            MANAGEMENT_HEADER_LENGTH
+        };
+        let body = if data.len() > offset {
+            &data[offset..]
+        } else {
+            &[]
+        };
+        let field = |index: usize| u16::unpack(&body[(index * 2).min(body.len())..]).ok();
+        let mut status = None;
+        let mut reason = None;
+        let mut authentication = None;
+        match control.get_subtype() {
+            FrameSubtype::Authentication => {
+                if let (Some(algorithm), Some(transaction)) = (field(0), field(1)) {
+                    authentication = Some((algorithm, transaction));
+                }
+                status = field(2).map(StatusCode::from);
+            }
+            FrameSubtype::AssociationResponse | FrameSubtype::ReassociationResponse => {
+                status = field(1).map(StatusCode::from);
+            }
+            FrameSubtype::Deauthentication | FrameSubtype::Disassociation => {
+                reason = field(0).map(ReasonCode::from);
+            }
+            _ => (),
+        }
+        Ok(MlmeFrame {
+            source,
+            bssid,
+            status,
+            reason,
+            authentication,
+            data: data.to_vec(),
+        })
+    }
+}
+
+/// MLME notification
+#[derive(Clone, Debug, PartialEq)]
+pub enum MlmeEvent {
+    /// Authentication frame received
+    Authenticate(MlmeFrame),
+    /// No authentication response received from the BSS
+    AuthenticateTimeout(HardwareAddress),
+    /// Association response received, with the information elements of the request
+    Associate(MlmeFrame, Vec<u8>),
+    /// No association response received from the BSS
+    AssociateTimeout(HardwareAddress),
+    /// Deauthentication frame sent or received
+    Deauthenticate(MlmeFrame),
+    /// Disassociation frame sent or received
+    Disassociate(MlmeFrame),
+    /// Unprotected deauthentication frame dropped because management frame protection is used
+    UnprotectedDeauthenticate(MlmeFrame),
+    /// Unprotected disassociation frame dropped because management frame protection is used
+    UnprotectedDisassociate(MlmeFrame),
+}
+
+impl MlmeEvent {
+    /// Parse a MLME event, `None` if the message is some other command
+    pub fn from_message(message: &generic::Message) -> Option<MlmeEvent> {
+        let command = Command::convert_from(message.command)?;
+        let mut frame = None;
+        let mut mac = None;
+        let mut timed_out = false;
+        let mut request_ies = vec![];
+        for attr in &message.attributes {
+            match Attribute::convert_from(attr.identifier) {
+                Some(Attribute::Frame) => {
+                    frame = MlmeFrame::unpack(&attr.as_bytes()).ok();
+                }
+                Some(Attribute::Mac) => {
+                    mac = attr.as_hardware_address().ok();
+                }
+                Some(Attribute::TimedOut) => {
+                    timed_out = true;
+                }
+                Some(Attribute::ReqIe) => {
+                    request_ies = attr.as_bytes();
+                }
+                _ => (),
+            }
+        }
+        if timed_out {
+            return match command {
+                Command::Authenticate => mac.map(MlmeEvent::AuthenticateTimeout),
+                Command::Associate => mac.map(MlmeEvent::AssociateTimeout),
+                _ => None,
+            };
+        }
+        let frame = frame?;
+        match command {
+            Command::Authenticate => Some(MlmeEvent::Authenticate(frame)),
+            Command::Associate => Some(MlmeEvent::Associate(frame, request_ies)),
+            Command::Deauthenticate => Some(MlmeEvent::Deauthenticate(frame)),
+            Command::Disassociate => Some(MlmeEvent::Disassociate(frame)),
+            Command::UnprotectedDeauthenticate => Some(MlmeEvent::UnprotectedDeauthenticate(frame)),
+            Command::UnprotectedDisassociate => Some(MlmeEvent::UnprotectedDisassociate(frame)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MlmeEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MlmeEvent::Authenticate(ref frame) => {
+                write!(f, "Authentication from {}", frame.source)?;
+                if let Some(status) = frame.status {
+                    write!(f, ": {}", status)?;
+                }
+                Ok(())
+            }
+            MlmeEvent::AuthenticateTimeout(ref bssid) => {
+                write!(f, "Authentication with {} timed out", bssid)
+            }
+            MlmeEvent::Associate(ref frame, _) => {
+                write!(f, "Association with {}", frame.source)?;
+                if let Some(status) = frame.status {
+                    write!(f, ": {}", status)?;
+                }
+                Ok(())
+            }
+            MlmeEvent::AssociateTimeout(ref bssid) => {
+                write!(f, "Association with {} timed out", bssid)
+            }
+            MlmeEvent::Deauthenticate(ref frame)
+            | MlmeEvent::UnprotectedDeauthenticate(ref frame) => {
+                write!(f, "Deauthentication from {}", frame.source)?;
+                if let Some(reason) = frame.reason {
+                    write!(f, ": {}", reason)?;
+                }
+                Ok(())
+            }
+            MlmeEvent::Disassociate(ref frame) | MlmeEvent::UnprotectedDisassociate(ref frame) => {
+                write!(f, "Disassociation from {}", frame.source)?;
+                if let Some(reason) = frame.reason {
+                    write!(f, ": {}", reason)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpack_authentication_frame() {
+        let bytes = [
+            0xb0, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x00, 0x03, 0x00, 0x02, 0x00,
+            0x01, 0x00,
+        ];
+        let frame = MlmeFrame::unpack(&bytes).unwrap();
+        assert_eq!(frame.authentication, Some((3, 2)));
+        assert_eq!(frame.status, Some(StatusCode::UnspecifiedFailure));
+        assert_eq!(frame.reason, None);
+    }
+
+    #[test]
+    fn test_unpack_deauthentication_frame() {
+        let bytes = [
+            0xc0, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x00, 0x0f, 0x00,
+        ];
+        let frame = MlmeFrame::unpack(&bytes).unwrap();
+        assert_eq!(frame.reason, Some(ReasonCode::FourWayHandshakeTimeout));
+        assert_eq!(frame.status, None);
+    }
+}
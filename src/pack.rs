@@ -0,0 +1,95 @@
+use byteorder::{ByteOrder, NativeEndian};
+
+use netlink_rust as netlink;
+
+const ATTRIBUTE_HEADER_SIZE: usize = 4;
+
+fn align(length: usize) -> usize {
+    (length + 3) & !3
+}
+
+/// Create a flag attribute, an attribute without payload
+pub fn flag_attribute<ID: Into<u16>>(identifier: ID) -> netlink::Attribute {
+    netlink::Attribute::new_bytes(identifier, &[])
+}
+
+/// Create an attribute with the provided attributes as nested payload
+pub fn nested_attribute<ID: Into<u16>>(
+    identifier: ID,
+    attributes: &[netlink::Attribute],
+) -> netlink::Attribute {
+    let mut data = vec![];
+    for attribute in attributes {
+        let payload = attribute.as_bytes();
+        let length = ATTRIBUTE_HEADER_SIZE + payload.len();
+        let mut header = [0u8; ATTRIBUTE_HEADER_SIZE];
+        NativeEndian::write_u16(&mut header[0..2], length as u16);
+        NativeEndian::write_u16(&mut header[2..4], attribute.identifier);
+        data.extend_from_slice(&header);
+        data.extend_from_slice(&payload);
+        data.resize(data.len() + align(length) - length, 0);
+    }
+    netlink::Attribute::new_bytes(identifier, &data)
+}
+
+/// Create a nested attribute array where each item is identified by its one based index
+pub fn nested_attribute_array<ID: Into<u16>>(
+    identifier: ID,
+    items: Vec<netlink::Attribute>,
+) -> netlink::Attribute {
+    let attributes: Vec<netlink::Attribute> = items
+        .into_iter()
+        .enumerate()
+        .map(|(n, item)| netlink::Attribute::new_bytes((n + 1) as u16, &item.as_bytes()))
+        .collect();
+    nested_attribute(identifier, &attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_flag() {
+        let attr = flag_attribute(7u16);
+        assert_eq!(attr.identifier, 7u16);
+        assert_eq!(attr.len(), 0);
+    }
+
+    #[test]
+    fn pack_nested() {
+        let attr = nested_attribute(
+            3u16,
+            &[
+                netlink::Attribute::new_bytes(1u16, &[0xaa]),
+                netlink::Attribute::new_bytes(2u16, &[0x01, 0x02, 0x03, 0x04]),
+            ],
+        );
+        assert_eq!(attr.identifier, 3u16);
+        let data = attr.as_bytes();
+        assert_eq!(data.len(), 16);
+        assert_eq!(NativeEndian::read_u16(&data[0..]), 5);
+        assert_eq!(NativeEndian::read_u16(&data[2..]), 1);
+        assert_eq!(&data[4..8], &[0xaa, 0, 0, 0]);
+        assert_eq!(NativeEndian::read_u16(&data[8..]), 8);
+        assert_eq!(NativeEndian::read_u16(&data[10..]), 2);
+        assert_eq!(&data[12..16], &[0x01, 0x02, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn pack_nested_array() {
+        let attr = nested_attribute_array(
+            45u16,
+            vec![
+                netlink::Attribute::new_bytes(0u16, b"ab"),
+                netlink::Attribute::new_bytes(0u16, b""),
+            ],
+        );
+        let data = attr.as_bytes();
+        assert_eq!(data.len(), 12);
+        assert_eq!(NativeEndian::read_u16(&data[2..]), 1);
+        assert_eq!(&data[4..6], b"ab");
+        assert_eq!(NativeEndian::read_u16(&data[8..]), 4);
+        assert_eq!(NativeEndian::read_u16(&data[10..]), 2);
+    }
+}
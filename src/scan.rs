@@ -0,0 +1,594 @@
+//! ## Scanning
+//!
+//! Parameters for scan and scheduled scan requests.
+
+use std::io;
+
+use crate::attributes::{Attribute, Band, SchedScanMatchAttribute, SchedScanPlanAttribute};
+use crate::commands::Command;
+use crate::pack::{flag_attribute, nested_attribute, nested_attribute_array};
+use crate::wireless_phy::{ExtendedFeaturesFlags, FeatureFlags, WirelessPhy};
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{ConvertFrom, Error, HardwareAddress};
+
+bitflags! {
+    /// Scan request control flags
+    pub struct ScanFlags: u32 {
+        /// Scan request has low priority
+        const LOW_PRIORITY                       = 1 << 0;
+        /// Flush cache before scanning
+        const FLUSH                              = 1 << 1;
+        /// Force a scan even if the interface is configured as AP
+        const AP                                 = 1 << 2;
+        /// Use a random MAC address for the scan
+        const RANDOM_ADDR                        = 1 << 3;
+        /// Fill the dwell time in the FILS request parameters IE
+        const FILS_MAX_CHANNEL_TIME              = 1 << 4;
+        /// Accept broadcast probe responses
+        const ACCEPT_BCAST_PROBE_RESP            = 1 << 5;
+        /// Send probe requests at a rate of at least 5.5 Mbps
+        const OCE_PROBE_REQ_HIGH_TX_RATE         = 1 << 6;
+        /// Allow probe request transmission deferral and suppression
+        const OCE_PROBE_REQ_DEFERRAL_SUPPRESSION = 1 << 7;
+        /// Span the scan over as short time as possible
+        const LOW_SPAN                           = 1 << 8;
+        /// Scan using as little power as possible
+        const LOW_POWER                          = 1 << 9;
+        /// Scan with the best possible accuracy
+        const HIGH_ACCURACY                      = 1 << 10;
+        /// Randomize the sequence number of probe requests
+        const RANDOM_SN                          = 1 << 11;
+        /// Use minimal probe request content
+        const MIN_PREQ_CONTENT                   = 1 << 12;
+    }
+}
+
+impl ScanFlags {
+    /// Check that the flags are supported by the phy
+    fn validate(self, phy: &WirelessPhy) -> Result<(), Error> {
+        let features = [
+            (ScanFlags::LOW_PRIORITY, FeatureFlags::LOW_PRIORITY_SCAN),
+            (ScanFlags::FLUSH, FeatureFlags::SCAN_FLUSH),
+            (ScanFlags::AP, FeatureFlags::AP_SCAN),
+            (ScanFlags::RANDOM_ADDR, FeatureFlags::SCAN_RANDOM_MAC_ADDR),
+        ];
+        let extended_features = [
+            (
+                ScanFlags::FILS_MAX_CHANNEL_TIME,
+                ExtendedFeaturesFlags::FILS_MAX_CHANNEL_TIME,
+            ),
+            (
+                ScanFlags::ACCEPT_BCAST_PROBE_RESP,
+                ExtendedFeaturesFlags::ACCEPT_BCAST_PROBE_RESP,
+            ),
+            (
+                ScanFlags::OCE_PROBE_REQ_HIGH_TX_RATE,
+                ExtendedFeaturesFlags::OCE_PROBE_REQ_HIGH_TX_RATE,
+            ),
+            (
+                ScanFlags::OCE_PROBE_REQ_DEFERRAL_SUPPRESSION,
+                ExtendedFeaturesFlags::OCE_PROBE_REQ_DEFERRAL_SUPPRESSION,
+            ),
+            (ScanFlags::LOW_SPAN, ExtendedFeaturesFlags::LOW_SPAN_SCAN),
+            (ScanFlags::LOW_POWER, ExtendedFeaturesFlags::LOW_POWER_SCAN),
+            (
+                ScanFlags::HIGH_ACCURACY,
+                ExtendedFeaturesFlags::HIGH_ACCURACY_SCAN,
+            ),
+            (ScanFlags::RANDOM_SN, ExtendedFeaturesFlags::SCAN_RANDOM_SN),
+            (
+                ScanFlags::MIN_PREQ_CONTENT,
+                ExtendedFeaturesFlags::SCAN_MIN_PREQ_CONTENT,
+            ),
+        ];
+        let supported = features
+            .iter()
+            .all(|&(flag, feature)| !self.contains(flag) || phy.features().contains(feature))
+            && extended_features.iter().all(|&(flag, feature)| {
+                !self.contains(flag) || phy.extended_features().contains(feature)
+            });
+        if supported {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput, "Unsupported scan flags").into())
+        }
+    }
+}
+
+/// Scan request parameters
+///
+/// An empty request scans all supported frequencies with a wildcard SSID.
+#[derive(Clone, Default)]
+pub struct ScanRequest {
+    ssids: Vec<Vec<u8>>,
+    frequencies: Vec<u32>,
+    flags: Option<ScanFlags>,
+    random_mac: Option<(HardwareAddress, HardwareAddress)>,
+    information_elements: Vec<u8>,
+    dwell_time: Option<(u16, bool)>,
+    bssid: Option<HardwareAddress>,
+    supported_rates: Vec<(Band, Vec<u8>)>,
+}
+
+impl ScanRequest {
+    pub fn new() -> ScanRequest {
+        ScanRequest::default()
+    }
+
+    /// Add a SSID to probe for, an empty SSID is a wildcard
+    pub fn ssid(mut self, ssid: &[u8]) -> ScanRequest {
+        self.ssids.push(ssid.to_vec());
+        self
+    }
+
+    /// Add a frequency in MHz to scan, all supported frequencies are scanned if none is given
+    pub fn frequency(mut self, frequency: u32) -> ScanRequest {
+        self.frequencies.push(frequency);
+        self
+    }
+
+    pub fn flags(mut self, flags: ScanFlags) -> ScanRequest {
+        self.flags = Some(self.flags.unwrap_or_else(ScanFlags::empty) | flags);
+        self
+    }
+
+    /// Use a random source address, bits set in `mask` are taken from `address`
+    pub fn random_mac(mut self, address: HardwareAddress, mask: HardwareAddress) -> ScanRequest {
+        self.random_mac = Some((address, mask));
+        self.flags(ScanFlags::RANDOM_ADDR)
+    }
+
+    /// Information elements to add to the probe requests
+    pub fn information_elements(mut self, data: &[u8]) -> ScanRequest {
+        self.information_elements = data.to_vec();
+        self
+    }
+
+    /// Dwell time in time units (1024 µs), `mandatory` tells that the duration must be honoured
+    pub fn dwell_time(mut self, duration: u16, mandatory: bool) -> ScanRequest {
+        self.dwell_time = Some((duration, mandatory));
+        self
+    }
+
+    /// Only report results from, and direct probe requests to, the given BSSID
+    pub fn bssid(mut self, bssid: HardwareAddress) -> ScanRequest {
+        self.bssid = Some(bssid);
+        self
+    }
+
+    /// Rates in units of 500 kbps to use for probe requests on the given band
+    pub fn supported_rates(mut self, band: Band, rates: &[u8]) -> ScanRequest {
+        self.supported_rates.push((band, rates.to_vec()));
+        self
+    }
+
+    /// Check the request against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        if self.ssids.len() > usize::from(phy.max_num_scan_ssids()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Too many SSIDs").into());
+        }
+        if self.information_elements.len() > usize::from(phy.max_scan_ie_len()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Information elements too long",
+            )
+            .into());
+        }
+        if self.dwell_time.is_some()
+            && !phy
+                .extended_features()
+                .contains(ExtendedFeaturesFlags::SET_SCAN_DWELL)
+        {
+            return Err(
+                io::Error::new(io::ErrorKind::InvalidInput, "Dwell time not supported").into(),
+            );
+        }
+        if let Some(flags) = self.flags {
+            flags.validate(phy)?;
+        }
+        Ok(())
+    }
+
+    /// Append the request parameters to a scan message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        if !self.ssids.is_empty() {
+            let ssids = self
+                .ssids
+                .iter()
+                .map(|ssid| netlink::Attribute::new_bytes(0u16, ssid))
+                .collect();
+            message.append_attribute(nested_attribute_array(Attribute::ScanSsids, ssids));
+        }
+        if !self.frequencies.is_empty() {
+            let frequencies = self
+                .frequencies
+                .iter()
+                .map(|&frequency| netlink::Attribute::new(0u16, frequency))
+                .collect();
+            message.append_attribute(nested_attribute_array(
+                Attribute::ScanFrequencies,
+                frequencies,
+            ));
+        }
+        if let Some(flags) = self.flags {
+            message.append_attribute(netlink::Attribute::new(Attribute::ScanFlags, flags.bits()));
+        }
+        if let Some((address, mask)) = self.random_mac {
+            message.append_attribute(netlink::Attribute::new(Attribute::Mac, address));
+            message.append_attribute(netlink::Attribute::new(Attribute::MacMask, mask));
+        }
+        if !self.information_elements.is_empty() {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::InformationElement,
+                &self.information_elements,
+            ));
+        }
+        if let Some((duration, mandatory)) = self.dwell_time {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::MeasurementDuration,
+                duration,
+            ));
+            if mandatory {
+                message.append_attribute(flag_attribute(Attribute::MeasurementDurationMandatory));
+            }
+        }
+        if let Some(bssid) = self.bssid {
+            message.append_attribute(netlink::Attribute::new(Attribute::Bssid, bssid));
+        }
+        if !self.supported_rates.is_empty() {
+            let rates: Vec<netlink::Attribute> = self
+                .supported_rates
+                .iter()
+                .map(|(band, rates)| netlink::Attribute::new_bytes(band.clone(), rates))
+                .collect();
+            message.append_attribute(nested_attribute(Attribute::ScanSuppRates, &rates));
+        }
+    }
+}
+
+/// Scheduled scan plan
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScanPlan {
+    /// Interval between scans in seconds
+    pub interval: u32,
+    /// Number of scans, `None` repeats the plan forever
+    pub iterations: Option<u32>,
+}
+
+/// Filter for which BSSs are reported by a scheduled scan
+///
+/// A BSS is reported when it matches all the criteria of any match set.
+#[derive(Clone, Default)]
+pub struct MatchSet {
+    ssid: Option<Vec<u8>>,
+    bssid: Option<HardwareAddress>,
+    rssi: Option<i32>,
+    band_rssi: Vec<(Band, i32)>,
+}
+
+impl MatchSet {
+    pub fn new() -> MatchSet {
+        MatchSet::default()
+    }
+
+    /// Only match BSSs with the given SSID
+    pub fn ssid(mut self, ssid: &[u8]) -> MatchSet {
+        self.ssid = Some(ssid.to_vec());
+        self
+    }
+
+    /// Only match the BSS with the given BSSID
+    pub fn bssid(mut self, bssid: HardwareAddress) -> MatchSet {
+        self.bssid = Some(bssid);
+        self
+    }
+
+    /// Only match BSSs with a signal strength of at least `rssi` dBm
+    pub fn rssi(mut self, rssi: i32) -> MatchSet {
+        self.rssi = Some(rssi);
+        self
+    }
+
+    /// Only match BSSs on the given band with a signal strength of at least `rssi` dBm
+    pub fn band_rssi(mut self, band: Band, rssi: i32) -> MatchSet {
+        self.band_rssi.push((band, rssi));
+        self
+    }
+
+    fn to_attribute(&self) -> netlink::Attribute {
+        let mut attrs = vec![];
+        if let Some(ref ssid) = self.ssid {
+            attrs.push(netlink::Attribute::new_bytes(
+                SchedScanMatchAttribute::Ssid,
+                ssid,
+            ));
+        }
+        if let Some(bssid) = self.bssid {
+            attrs.push(netlink::Attribute::new(
+                SchedScanMatchAttribute::Bssid,
+                bssid,
+            ));
+        }
+        if let Some(rssi) = self.rssi {
+            attrs.push(netlink::Attribute::new(SchedScanMatchAttribute::Rssi, rssi));
+        }
+        if !self.band_rssi.is_empty() {
+            let band_rssi: Vec<netlink::Attribute> = self
+                .band_rssi
+                .iter()
+                .map(|(band, rssi)| netlink::Attribute::new(band.clone(), *rssi))
+                .collect();
+            attrs.push(nested_attribute(
+                SchedScanMatchAttribute::PerBandRssi,
+                &band_rssi,
+            ));
+        }
+        nested_attribute(0u16, &attrs)
+    }
+}
+
+/// Scheduled scan request parameters
+///
+/// The device scans periodically according to the scan plans and reports results matching
+/// the match sets, if any, with `ScheduledScanEvent::Results`.
+#[derive(Clone, Default)]
+pub struct ScheduledScanRequest {
+    scan: ScanRequest,
+    plans: Vec<ScanPlan>,
+    match_sets: Vec<MatchSet>,
+    relative_rssi: Option<i8>,
+    rssi_adjust: Option<(Band, i8)>,
+    delay: Option<u32>,
+    multi: bool,
+}
+
+impl ScheduledScanRequest {
+    pub fn new() -> ScheduledScanRequest {
+        ScheduledScanRequest::default()
+    }
+
+    /// Add a SSID to probe for, an empty SSID is a wildcard
+    pub fn ssid(mut self, ssid: &[u8]) -> ScheduledScanRequest {
+        self.scan = self.scan.ssid(ssid);
+        self
+    }
+
+    /// Add a frequency in MHz to scan, all supported frequencies are scanned if none is given
+    pub fn frequency(mut self, frequency: u32) -> ScheduledScanRequest {
+        self.scan = self.scan.frequency(frequency);
+        self
+    }
+
+    pub fn flags(mut self, flags: ScanFlags) -> ScheduledScanRequest {
+        self.scan = self.scan.flags(flags);
+        self
+    }
+
+    /// Use a random source address, bits set in `mask` are taken from `address`
+    pub fn random_mac(
+        mut self,
+        address: HardwareAddress,
+        mask: HardwareAddress,
+    ) -> ScheduledScanRequest {
+        self.scan = self.scan.random_mac(address, mask);
+        self
+    }
+
+    /// Information elements to add to the probe requests
+    pub fn information_elements(mut self, data: &[u8]) -> ScheduledScanRequest {
+        self.scan = self.scan.information_elements(data);
+        self
+    }
+
+    /// Add a scan plan, plans are executed in order
+    ///
+    /// Every plan except the last must have an iteration count; the last plan must have none
+    /// and repeats forever.
+    pub fn plan(mut self, interval: u32, iterations: Option<u32>) -> ScheduledScanRequest {
+        self.plans.push(ScanPlan {
+            interval,
+            iterations,
+        });
+        self
+    }
+
+    /// Add a match set, without match sets all results are reported
+    pub fn match_set(mut self, match_set: MatchSet) -> ScheduledScanRequest {
+        self.match_sets.push(match_set);
+        self
+    }
+
+    /// Only report BSSs with a signal stronger than the current BSS by `rssi` dB
+    pub fn relative_rssi(mut self, rssi: i8) -> ScheduledScanRequest {
+        self.relative_rssi = Some(rssi);
+        self
+    }
+
+    /// Adjust the signal of BSSs on `band` by `delta` dB when comparing with the relative RSSI
+    pub fn rssi_adjust(mut self, band: Band, delta: i8) -> ScheduledScanRequest {
+        self.rssi_adjust = Some((band, delta));
+        self
+    }
+
+    /// Delay in seconds before the first scan
+    pub fn delay(mut self, delay: u32) -> ScheduledScanRequest {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Run the scheduled scan alongside other scheduled scans
+    ///
+    /// The started scan is identified by the cookie returned when starting it.
+    pub fn multi(mut self) -> ScheduledScanRequest {
+        self.multi = true;
+        self
+    }
+
+    /// Request to run alongside other scheduled scans
+    pub fn is_multi(&self) -> bool {
+        self.multi
+    }
+
+    /// Check the request against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if self.plans.is_empty() {
+            return invalid("No scan plan");
+        }
+        if self.plans.len() > phy.max_num_sched_scan_plans() as usize {
+            return invalid("Too many scan plans");
+        }
+        let last = self.plans.len() - 1;
+        for (n, plan) in self.plans.iter().enumerate() {
+            if plan.interval == 0 || plan.interval > phy.max_scan_plan_interval() {
+                return invalid("Invalid scan plan interval");
+            }
+            match plan.iterations {
+                Some(_) if n == last => {
+                    return invalid("The last scan plan must be infinite");
+                }
+                Some(iterations) => {
+                    if iterations == 0 || iterations > phy.max_scan_plan_iterations() {
+                        return invalid("Invalid scan plan iterations");
+                    }
+                }
+                None => {
+                    if n != last {
+                        return invalid("Only the last scan plan may be infinite");
+                    }
+                }
+            }
+        }
+        if self.match_sets.len() > usize::from(phy.max_match_sets()) {
+            return invalid("Too many match sets");
+        }
+        if self.match_sets.iter().any(|m| !m.band_rssi.is_empty())
+            && !phy
+                .extended_features()
+                .contains(ExtendedFeaturesFlags::SCHED_SCAN_BAND_SPECIFIC_RSSI_THOLD)
+        {
+            return invalid("Band specific RSSI not supported");
+        }
+        if self.scan.ssids.len() > usize::from(phy.max_num_sched_scan_ssids()) {
+            return invalid("Too many SSIDs");
+        }
+        if self.scan.information_elements.len() > usize::from(phy.max_sched_scan_ie_len()) {
+            return invalid("Information elements too long");
+        }
+        if (self.relative_rssi.is_some() || self.rssi_adjust.is_some())
+            && !phy
+                .extended_features()
+                .contains(ExtendedFeaturesFlags::SCHED_SCAN_RELATIVE_RSSI)
+        {
+            return invalid("Relative RSSI not supported");
+        }
+        if self.rssi_adjust.is_some() && self.relative_rssi.is_none() {
+            return invalid("RSSI adjust requires relative RSSI");
+        }
+        if self.multi && phy.sched_scan_max_reqs() == 0 {
+            return invalid("Multiple scheduled scans not supported");
+        }
+        if let Some(flags) = self.scan.flags {
+            // Random addresses for scheduled scans have their own feature flag
+            if flags.contains(ScanFlags::RANDOM_ADDR)
+                && !phy
+                    .features()
+                    .contains(FeatureFlags::SCHED_SCAN_RANDOM_MAC_ADDR)
+            {
+                return invalid("Unsupported scan flags");
+            }
+            (flags - ScanFlags::RANDOM_ADDR).validate(phy)?;
+        }
+        Ok(())
+    }
+
+    /// Append the request parameters to a start scheduled scan message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        self.scan.append_attributes(message);
+        let plans = self
+            .plans
+            .iter()
+            .map(|plan| {
+                let mut attrs = vec![netlink::Attribute::new(
+                    SchedScanPlanAttribute::Interval,
+                    plan.interval,
+                )];
+                if let Some(iterations) = plan.iterations {
+                    attrs.push(netlink::Attribute::new(
+                        SchedScanPlanAttribute::Iterations,
+                        iterations,
+                    ));
+                }
+                nested_attribute(0u16, &attrs)
+            })
+            .collect();
+        message.append_attribute(nested_attribute_array(Attribute::SchedScanPlans, plans));
+        if !self.match_sets.is_empty() {
+            let match_sets = self.match_sets.iter().map(MatchSet::to_attribute).collect();
+            message.append_attribute(nested_attribute_array(
+                Attribute::SchedScanMatch,
+                match_sets,
+            ));
+        }
+        if let Some(rssi) = self.relative_rssi {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::SchedScanRelativeRssi,
+                rssi,
+            ));
+        }
+        if let Some((ref band, delta)) = self.rssi_adjust {
+            // struct nl80211_bss_select_rssi_adjust
+            let data = [u16::from(band.clone()) as u8, delta as u8];
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::SchedScanRssiAdjust,
+                &data,
+            ));
+        }
+        if let Some(delay) = self.delay {
+            message.append_attribute(netlink::Attribute::new(Attribute::SchedScanDelay, delay));
+        }
+        if self.multi {
+            message.append_attribute(flag_attribute(Attribute::SchedScanMulti));
+        }
+    }
+}
+
+/// Scheduled scan notification
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScheduledScanEvent {
+    /// A scheduled scan was started
+    Started(Option<u64>),
+    /// A scheduled scan has results available
+    Results(Option<u64>),
+    /// A scheduled scan was stopped
+    Stopped(Option<u64>),
+}
+
+impl ScheduledScanEvent {
+    /// Parse a scheduled scan event, `None` if the message is some other command
+    ///
+    /// The value is the cookie identifying the scan when it was started with `multi`.
+    pub fn from_message(message: &generic::Message) -> Option<ScheduledScanEvent> {
+        let cookie = message
+            .attributes
+            .iter()
+            .find(|attr| Attribute::convert_from(attr.identifier) == Some(Attribute::Cookie))
+            .and_then(|attr| attr.as_u64().ok());
+        match Command::convert_from(message.command) {
+            Some(Command::StartScheduledScan) => Some(ScheduledScanEvent::Started(cookie)),
+            Some(Command::ScheduledScanResults) => Some(ScheduledScanEvent::Results(cookie)),
+            Some(Command::ScheduledScanStopped) => Some(ScheduledScanEvent::Stopped(cookie)),
+            _ => None,
+        }
+    }
+
+    /// The cookie identifying the scheduled scan
+    pub fn cookie(&self) -> Option<u64> {
+        match *self {
+            ScheduledScanEvent::Started(cookie)
+            | ScheduledScanEvent::Results(cookie)
+            | ScheduledScanEvent::Stopped(cookie) => cookie,
+        }
+    }
+}
@@ -0,0 +1,1143 @@
+//! ## Station
+//!
+//! Information about peers of the wireless device, such as the access point a station is
+//! associated with or the stations associated with an access point.
+
+use std::fmt;
+use std::io;
+
+use byteorder::{ByteOrder, NativeEndian};
+
+use crate::attributes::{
+    Attribute, EhtGuardInterval, EhtResourceUnitAllocation, HeGuardInterval,
+    HeResourceUnitAllocation, MeshPowerMode, PeerLinkState, RateInfoAttribute,
+    StationBssParameterAttribute, StationInformationAttributes, StationWmeAttribute,
+    TidStatisticsAttribute, TransmitQueueStatisticsAttribute,
+};
+use crate::pack::nested_attribute;
+use crate::status::ReasonCode;
+use crate::wireless_phy::{FeatureFlags, WirelessPhy};
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{ConvertFrom, Error, HardwareAddress};
+
+const MAX_SUPPORTED_RATES: usize = 32;
+const MAX_AID: u16 = 2007;
+const HT_CAPABILITY_LENGTH: usize = 26;
+const VHT_CAPABILITY_LENGTH: usize = 12;
+/// Management frame subtypes used when removing a station
+const SUBTYPE_DISASSOCIATION: u8 = 10;
+const SUBTYPE_DEAUTHENTICATION: u8 = 12;
+
+/// Parse a nested per chain signal strength attribute
+fn chain_signal(attr: &netlink::Attribute) -> Result<Vec<i8>, Error> {
+    let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+    let mut signals = vec![];
+    for attr in attrs {
+        signals.push(attr.as_i8()?);
+    }
+    Ok(signals)
+}
+
+/// Channel width used for transmitting a frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateWidth {
+    Mhz5,
+    Mhz10,
+    Mhz20,
+    Mhz40,
+    Mhz80,
+    Mhz80P80,
+    Mhz160,
+    Mhz320,
+}
+
+impl fmt::Display for RateWidth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RateWidth::Mhz5 => write!(f, "5MHz"),
+            RateWidth::Mhz10 => write!(f, "10MHz"),
+            RateWidth::Mhz20 => write!(f, "20MHz"),
+            RateWidth::Mhz40 => write!(f, "40MHz"),
+            RateWidth::Mhz80 => write!(f, "80MHz"),
+            RateWidth::Mhz80P80 => write!(f, "80+80MHz"),
+            RateWidth::Mhz160 => write!(f, "160MHz"),
+            RateWidth::Mhz320 => write!(f, "320MHz"),
+        }
+    }
+}
+
+/// Transmit or receive rate information
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateInfo {
+    /// Bitrate in units of 100 kbit/s, limited to 16 bits
+    bitrate16: Option<u16>,
+    /// Bitrate in units of 100 kbit/s
+    bitrate32: Option<u32>,
+    /// HT MCS index
+    pub mcs: Option<u8>,
+    /// VHT MCS index
+    pub vht_mcs: Option<u8>,
+    /// VHT number of spatial streams
+    pub vht_nss: Option<u8>,
+    /// HE MCS index
+    pub he_mcs: Option<u8>,
+    /// HE number of spatial streams
+    pub he_nss: Option<u8>,
+    /// HE guard interval
+    pub he_gi: Option<HeGuardInterval>,
+    /// HE dual carrier modulation
+    pub he_dcm: Option<bool>,
+    /// HE resource unit allocation
+    pub he_ru_alloc: Option<HeResourceUnitAllocation>,
+    /// EHT MCS index
+    pub eht_mcs: Option<u8>,
+    /// EHT number of spatial streams
+    pub eht_nss: Option<u8>,
+    /// EHT guard interval
+    pub eht_gi: Option<EhtGuardInterval>,
+    /// EHT resource unit allocation
+    pub eht_ru_alloc: Option<EhtResourceUnitAllocation>,
+    /// Channel width
+    pub width: RateWidth,
+    /// Short guard interval used (HT and VHT)
+    pub short_gi: bool,
+}
+
+impl RateInfo {
+    /// Parse the nested rate information attributes
+    pub fn from_attributes(attributes: &[netlink::Attribute]) -> Result<RateInfo, Error> {
+        let mut rate = RateInfo {
+            bitrate16: None,
+            bitrate32: None,
+            mcs: None,
+            vht_mcs: None,
+            vht_nss: None,
+            he_mcs: None,
+            he_nss: None,
+            he_gi: None,
+            he_dcm: None,
+            he_ru_alloc: None,
+            eht_mcs: None,
+            eht_nss: None,
+            eht_gi: None,
+            eht_ru_alloc: None,
+            width: RateWidth::Mhz20,
+            short_gi: false,
+        };
+        for attr in attributes {
+            let identifier = RateInfoAttribute::convert_from(attr.identifier);
+            if let Some(identifier) = identifier {
+                match identifier {
+                    RateInfoAttribute::Bitrate => {
+                        rate.bitrate16 = Some(attr.as_u16()?);
+                    }
+                    RateInfoAttribute::Bitrate32 => {
+                        rate.bitrate32 = Some(attr.as_u32()?);
+                    }
+                    RateInfoAttribute::Mcs => {
+                        rate.mcs = Some(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::VhtMcs => {
+                        rate.vht_mcs = Some(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::VhtNss => {
+                        rate.vht_nss = Some(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::HeMcs => {
+                        rate.he_mcs = Some(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::HeNss => {
+                        rate.he_nss = Some(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::HeGuardInterval => {
+                        rate.he_gi = HeGuardInterval::convert_from(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::HeDualCarrierModulation => {
+                        rate.he_dcm = Some(attr.as_u8()? != 0);
+                    }
+                    RateInfoAttribute::HeResourceUnitAllocation => {
+                        rate.he_ru_alloc = HeResourceUnitAllocation::convert_from(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::EhtMcs => {
+                        rate.eht_mcs = Some(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::EhtNss => {
+                        rate.eht_nss = Some(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::EhtGuardInterval => {
+                        rate.eht_gi = EhtGuardInterval::convert_from(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::EhtResourceUnitAllocation => {
+                        rate.eht_ru_alloc = EhtResourceUnitAllocation::convert_from(attr.as_u8()?);
+                    }
+                    RateInfoAttribute::Width5Mhz => rate.width = RateWidth::Mhz5,
+                    RateInfoAttribute::Width10Mhz => rate.width = RateWidth::Mhz10,
+                    RateInfoAttribute::Width40Mhz => rate.width = RateWidth::Mhz40,
+                    RateInfoAttribute::Width80Mhz => rate.width = RateWidth::Mhz80,
+                    RateInfoAttribute::Width80P80Mhz => rate.width = RateWidth::Mhz80P80,
+                    RateInfoAttribute::Width160Mhz => rate.width = RateWidth::Mhz160,
+                    RateInfoAttribute::Width320Mhz => rate.width = RateWidth::Mhz320,
+                    RateInfoAttribute::ShortGuardInterval => rate.short_gi = true,
+                    RateInfoAttribute::Invalid => (),
+                }
+            }
+        }
+        Ok(rate)
+    }
+
+    fn from_attribute(attr: &netlink::Attribute) -> Result<RateInfo, Error> {
+        let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+        RateInfo::from_attributes(&attrs)
+    }
+
+    /// Bitrate in kbit/s, `None` if the kernel could not calculate it
+    pub fn bitrate(&self) -> Option<u32> {
+        self.bitrate32
+            .or_else(|| self.bitrate16.map(u32::from))
+            .map(|bitrate| bitrate * 100)
+    }
+}
+
+impl fmt::Display for RateInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.bitrate() {
+            Some(bitrate) => write!(f, "{}.{} MBit/s", bitrate / 1000, (bitrate % 1000) / 100)?,
+            None => write!(f, "Unknown")?,
+        }
+        if let Some(mcs) = self.mcs {
+            write!(f, " MCS {}", mcs)?;
+        }
+        if let Some(mcs) = self.vht_mcs {
+            write!(f, " VHT-MCS {}", mcs)?;
+        }
+        if let Some(nss) = self.vht_nss {
+            write!(f, " VHT-NSS {}", nss)?;
+        }
+        if let Some(mcs) = self.he_mcs {
+            write!(f, " HE-MCS {}", mcs)?;
+        }
+        if let Some(nss) = self.he_nss {
+            write!(f, " HE-NSS {}", nss)?;
+        }
+        if let Some(mcs) = self.eht_mcs {
+            write!(f, " EHT-MCS {}", mcs)?;
+        }
+        if let Some(nss) = self.eht_nss {
+            write!(f, " EHT-NSS {}", nss)?;
+        }
+        write!(f, " {}", self.width)?;
+        if self.short_gi {
+            write!(f, " short GI")?;
+        }
+        Ok(())
+    }
+}
+
+/// BSS parameters of the station
+#[derive(Debug, Clone, PartialEq)]
+pub struct BssParam {
+    /// CTS protection is enabled
+    pub cts_protection: bool,
+    /// Short preamble is enabled
+    pub short_preamble: bool,
+    /// Short slot time is enabled
+    pub short_slot_time: bool,
+    /// DTIM period of the BSS
+    pub dtim_period: Option<u8>,
+    /// Beacon interval in time units (1024 µs)
+    pub beacon_interval: Option<u16>,
+}
+
+impl BssParam {
+    /// Parse the nested BSS parameter attributes
+    pub fn from_attributes(attributes: &[netlink::Attribute]) -> Result<BssParam, Error> {
+        let mut param = BssParam {
+            cts_protection: false,
+            short_preamble: false,
+            short_slot_time: false,
+            dtim_period: None,
+            beacon_interval: None,
+        };
+        for attr in attributes {
+            let identifier = StationBssParameterAttribute::convert_from(attr.identifier);
+            if let Some(identifier) = identifier {
+                match identifier {
+                    StationBssParameterAttribute::CtsProtection => param.cts_protection = true,
+                    StationBssParameterAttribute::ShortPreamble => param.short_preamble = true,
+                    StationBssParameterAttribute::ShortSlotTime => param.short_slot_time = true,
+                    StationBssParameterAttribute::DtimPeriod => {
+                        param.dtim_period = Some(attr.as_u8()?);
+                    }
+                    StationBssParameterAttribute::BeaconInterval => {
+                        param.beacon_interval = Some(attr.as_u16()?);
+                    }
+                    StationBssParameterAttribute::Invalid => (),
+                }
+            }
+        }
+        Ok(param)
+    }
+}
+
+bitflags! {
+    /// Station flags, bit n corresponds to station flag n of nl80211
+    pub struct StationFlags: u32 {
+        /// Station is authorized to send and receive data frames
+        const AUTHORIZED     = 1 << 1;
+        /// Station is capable of receiving frames with short preamble
+        const SHORT_PREAMBLE = 1 << 2;
+        /// Station is WME/QoS capable
+        const WME            = 1 << 3;
+        /// Station uses management frame protection
+        const MFP            = 1 << 4;
+        /// Station is authenticated
+        const AUTHENTICATED  = 1 << 5;
+        /// Station is a TDLS peer
+        const TDLS_PEER      = 1 << 6;
+        /// Station is associated
+        const ASSOCIATED     = 1 << 7;
+        /// Station supports signaling and payload protected A-MSDUs
+        const SPP_AMSDU      = 1 << 8;
+    }
+}
+
+/// Station flags as a mask and set pair
+///
+/// Only the flags in `mask` are valid, of those the flags in `set` are set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StationFlagUpdate {
+    pub mask: StationFlags,
+    pub set: StationFlags,
+}
+
+impl StationFlagUpdate {
+    /// Parse from struct nl80211_sta_flag_update
+    pub fn from_bytes(data: &[u8]) -> Result<StationFlagUpdate, Error> {
+        if data.len() < 8 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid station flags").into());
+        }
+        Ok(StationFlagUpdate {
+            mask: StationFlags::from_bits_truncate(NativeEndian::read_u32(&data[0..4])),
+            set: StationFlags::from_bits_truncate(NativeEndian::read_u32(&data[4..8])),
+        })
+    }
+
+    /// State of the flag, `None` if the flag is not in the mask
+    pub fn get(&self, flag: StationFlags) -> Option<bool> {
+        if self.mask.contains(flag) {
+            Some(self.set.contains(flag))
+        } else {
+            None
+        }
+    }
+
+    /// Add the flag to the mask, setting or clearing it
+    pub fn with(mut self, flag: StationFlags, value: bool) -> StationFlagUpdate {
+        self.mask.insert(flag);
+        self.set.set(flag, value);
+        self
+    }
+
+    /// Pack as struct nl80211_sta_flag_update
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let mut data = [0u8; 8];
+        NativeEndian::write_u32(&mut data[0..4], self.mask.bits());
+        NativeEndian::write_u32(&mut data[4..8], self.set.bits());
+        data
+    }
+}
+
+impl Default for StationFlagUpdate {
+    fn default() -> StationFlagUpdate {
+        StationFlagUpdate {
+            mask: StationFlags::empty(),
+            set: StationFlags::empty(),
+        }
+    }
+}
+
+/// Transmit queue statistics
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TxqStats {
+    /// Bytes waiting in the queue
+    pub backlog_bytes: Option<u32>,
+    /// Packets waiting in the queue
+    pub backlog_packets: Option<u32>,
+    /// Number of active flows
+    pub flows: Option<u32>,
+    /// Packets dropped
+    pub drops: Option<u32>,
+    /// Packets marked with ECN
+    pub ecn_marks: Option<u32>,
+    /// Packets dropped because the queue limit was exceeded
+    pub overlimit: Option<u32>,
+    /// Packets dropped because the memory limit was exceeded
+    pub overmemory: Option<u32>,
+    /// Hash collisions
+    pub collisions: Option<u32>,
+    /// Transmitted bytes
+    pub tx_bytes: Option<u32>,
+    /// Transmitted packets
+    pub tx_packets: Option<u32>,
+    /// Maximum number of flows
+    pub max_flows: Option<u32>,
+}
+
+impl TxqStats {
+    /// Parse the nested transmit queue statistics attributes
+    pub fn from_attributes(attributes: &[netlink::Attribute]) -> Result<TxqStats, Error> {
+        let mut stats = TxqStats::default();
+        for attr in attributes {
+            let identifier = TransmitQueueStatisticsAttribute::convert_from(attr.identifier);
+            if let Some(identifier) = identifier {
+                match identifier {
+                    TransmitQueueStatisticsAttribute::BacklogBytes => {
+                        stats.backlog_bytes = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::BacklogPackets => {
+                        stats.backlog_packets = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::Flows => {
+                        stats.flows = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::Drops => {
+                        stats.drops = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::EcnMarks => {
+                        stats.ecn_marks = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::Overlimit => {
+                        stats.overlimit = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::Overmemory => {
+                        stats.overmemory = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::Collisions => {
+                        stats.collisions = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::TransmittedBytes => {
+                        stats.tx_bytes = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::TransmittedPackets => {
+                        stats.tx_packets = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::MaximumFlows => {
+                        stats.max_flows = Some(attr.as_u32()?);
+                    }
+                    TransmitQueueStatisticsAttribute::Invalid => (),
+                }
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Per traffic identifier statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct TidStats {
+    /// Traffic identifier, 0 - 15, or 16 for non-QoS traffic
+    pub tid: u8,
+    /// Received MSDUs
+    pub rx_msdu: Option<u64>,
+    /// Transmitted MSDUs
+    pub tx_msdu: Option<u64>,
+    /// Transmit retries of MSDUs
+    pub tx_msdu_retries: Option<u64>,
+    /// Failed transmissions of MSDUs
+    pub tx_msdu_failed: Option<u64>,
+    /// Statistics of the transmit queue of the traffic identifier
+    pub txq_stats: Option<TxqStats>,
+}
+
+impl TidStats {
+    /// Parse the nested TID statistics attributes
+    pub fn from_attributes(tid: u8, attributes: &[netlink::Attribute]) -> Result<TidStats, Error> {
+        let mut stats = TidStats {
+            tid,
+            rx_msdu: None,
+            tx_msdu: None,
+            tx_msdu_retries: None,
+            tx_msdu_failed: None,
+            txq_stats: None,
+        };
+        for attr in attributes {
+            let identifier = TidStatisticsAttribute::convert_from(attr.identifier);
+            if let Some(identifier) = identifier {
+                match identifier {
+                    TidStatisticsAttribute::ReceivedMsdu => {
+                        stats.rx_msdu = Some(attr.as_u64()?);
+                    }
+                    TidStatisticsAttribute::TransmittedMsdu => {
+                        stats.tx_msdu = Some(attr.as_u64()?);
+                    }
+                    TidStatisticsAttribute::TransmitMsduRetries => {
+                        stats.tx_msdu_retries = Some(attr.as_u64()?);
+                    }
+                    TidStatisticsAttribute::TransmitMsduFailures => {
+                        stats.tx_msdu_failed = Some(attr.as_u64()?);
+                    }
+                    TidStatisticsAttribute::TransmitQueueStatistics => {
+                        let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                        stats.txq_stats = Some(TxqStats::from_attributes(&attrs)?);
+                    }
+                    TidStatisticsAttribute::Invalid | TidStatisticsAttribute::Padding => (),
+                }
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Station information and statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct StationInfo {
+    /// MAC address of the station
+    pub mac: HardwareAddress,
+    /// Milliseconds since the last activity
+    pub inactive_time: Option<u32>,
+    /// Received bytes, from the 64-bit counter if available
+    pub rx_bytes: Option<u64>,
+    /// Transmitted bytes, from the 64-bit counter if available
+    pub tx_bytes: Option<u64>,
+    /// Received packets
+    pub rx_packets: Option<u32>,
+    /// Transmitted packets
+    pub tx_packets: Option<u32>,
+    /// Transmit retries
+    pub tx_retries: Option<u32>,
+    /// Failed transmissions
+    pub tx_failed: Option<u32>,
+    /// Number of beacons lost
+    pub beacon_loss: Option<u32>,
+    /// Seconds since the station connected
+    pub connected_time: Option<u32>,
+    /// Signal strength of the last received frame in dBm
+    pub signal: Option<i8>,
+    /// Average signal strength in dBm
+    pub signal_average: Option<i8>,
+    /// Per chain signal strength of the last received frame in dBm
+    pub chain_signal: Vec<i8>,
+    /// Per chain average signal strength in dBm
+    pub chain_signal_average: Vec<i8>,
+    /// Timing offset in microseconds
+    pub timing_offset: Option<i64>,
+    /// Expected throughput in kbit/s
+    pub expected_throughput: Option<u32>,
+    /// Rate of the last transmitted unicast frame
+    pub tx_bitrate: Option<RateInfo>,
+    /// Rate of the last received unicast frame
+    pub rx_bitrate: Option<RateInfo>,
+    /// Received packets dropped for unspecified reasons
+    pub rx_dropped: Option<u64>,
+    /// Received beacons
+    pub beacon_rx: Option<u64>,
+    /// Average beacon signal strength in dBm
+    pub beacon_signal_average: Option<i8>,
+    /// Aggregate receive airtime in microseconds
+    pub rx_duration: Option<u64>,
+    /// Aggregate transmit airtime in microseconds
+    pub tx_duration: Option<u64>,
+    /// Signal strength of the last received ACK frame in dBm
+    pub ack_signal: Option<i8>,
+    /// Average ACK signal strength in dBm
+    pub ack_signal_average: Option<i8>,
+    /// Received MPDUs
+    pub rx_mpdus: Option<u32>,
+    /// Received MPDUs with a frame check sequence error
+    pub fcs_error_count: Option<u32>,
+    /// Airtime weight used by the airtime scheduler
+    pub airtime_weight: Option<u16>,
+    /// Airtime link metric for mesh path selection
+    pub airtime_link_metric: Option<u32>,
+    /// Time of association in CLOCK_BOOTTIME, nanoseconds
+    pub assoc_at_boottime: Option<u64>,
+    /// The mesh station is connected to a mesh gate
+    pub connected_to_gate: Option<bool>,
+    /// The mesh station is connected to an authentication server
+    pub connected_to_as: Option<bool>,
+    /// BSS parameters
+    pub bss_param: Option<BssParam>,
+    /// Station flags
+    pub flags: Option<StationFlagUpdate>,
+    /// Per traffic identifier statistics
+    pub tid_stats: Vec<TidStats>,
+    /// Mesh local link identifier
+    pub llid: Option<u16>,
+    /// Mesh peer link identifier
+    pub plid: Option<u16>,
+    /// Mesh peer link state
+    pub plink_state: Option<PeerLinkState>,
+    /// Local mesh power mode towards the peer
+    pub local_pm: Option<MeshPowerMode>,
+    /// Mesh power mode of the peer towards the local station
+    pub peer_pm: Option<MeshPowerMode>,
+    /// Mesh power mode towards non-peer stations
+    pub nonpeer_pm: Option<MeshPowerMode>,
+}
+
+impl StationInfo {
+    /// Parse the nested station information attributes
+    pub fn from_attributes(
+        mac: HardwareAddress,
+        attributes: &[netlink::Attribute],
+    ) -> Result<StationInfo, Error> {
+        let mut station = StationInfo {
+            mac,
+            inactive_time: None,
+            rx_bytes: None,
+            tx_bytes: None,
+            rx_packets: None,
+            tx_packets: None,
+            tx_retries: None,
+            tx_failed: None,
+            beacon_loss: None,
+            connected_time: None,
+            signal: None,
+            signal_average: None,
+            chain_signal: vec![],
+            chain_signal_average: vec![],
+            timing_offset: None,
+            expected_throughput: None,
+            tx_bitrate: None,
+            rx_bitrate: None,
+            rx_dropped: None,
+            beacon_rx: None,
+            beacon_signal_average: None,
+            rx_duration: None,
+            tx_duration: None,
+            ack_signal: None,
+            ack_signal_average: None,
+            rx_mpdus: None,
+            fcs_error_count: None,
+            airtime_weight: None,
+            airtime_link_metric: None,
+            assoc_at_boottime: None,
+            connected_to_gate: None,
+            connected_to_as: None,
+            bss_param: None,
+            flags: None,
+            tid_stats: vec![],
+            llid: None,
+            plid: None,
+            plink_state: None,
+            local_pm: None,
+            peer_pm: None,
+            nonpeer_pm: None,
+        };
+        let mut rx_bytes64 = None;
+        let mut tx_bytes64 = None;
+        for attr in attributes {
+            let identifier = StationInformationAttributes::convert_from(attr.identifier);
+            if let Some(identifier) = identifier {
+                match identifier {
+                    StationInformationAttributes::InactiveTime => {
+                        station.inactive_time = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::ReceivedBytes => {
+                        station.rx_bytes = Some(u64::from(attr.as_u32()?));
+                    }
+                    StationInformationAttributes::TransmittedBytes => {
+                        station.tx_bytes = Some(u64::from(attr.as_u32()?));
+                    }
+                    StationInformationAttributes::ReceivedBytes64 => {
+                        rx_bytes64 = Some(attr.as_u64()?);
+                    }
+                    StationInformationAttributes::TransmittedBytes64 => {
+                        tx_bytes64 = Some(attr.as_u64()?);
+                    }
+                    StationInformationAttributes::ReceivedPackets => {
+                        station.rx_packets = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::TransmittedPackets => {
+                        station.tx_packets = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::TransmitRetries => {
+                        station.tx_retries = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::TransmitFailures => {
+                        station.tx_failed = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::BeaconLoss => {
+                        station.beacon_loss = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::ConnectedTime => {
+                        station.connected_time = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::Signal => {
+                        station.signal = Some(attr.as_i8()?);
+                    }
+                    StationInformationAttributes::SignalAverage => {
+                        station.signal_average = Some(attr.as_i8()?);
+                    }
+                    StationInformationAttributes::ChainSignal => {
+                        station.chain_signal = chain_signal(attr)?;
+                    }
+                    StationInformationAttributes::ChainSignalAverage => {
+                        station.chain_signal_average = chain_signal(attr)?;
+                    }
+                    StationInformationAttributes::TimingOffset => {
+                        station.timing_offset = Some(attr.as_i64()?);
+                    }
+                    StationInformationAttributes::ExpectedThroughput => {
+                        station.expected_throughput = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::TransmitBitrate => {
+                        station.tx_bitrate = Some(RateInfo::from_attribute(attr)?);
+                    }
+                    StationInformationAttributes::ReceiveBitrate => {
+                        station.rx_bitrate = Some(RateInfo::from_attribute(attr)?);
+                    }
+                    StationInformationAttributes::ReceiveDropped => {
+                        station.rx_dropped = Some(attr.as_u64()?);
+                    }
+                    StationInformationAttributes::ReceivedBeacons => {
+                        station.beacon_rx = Some(attr.as_u64()?);
+                    }
+                    StationInformationAttributes::BeaconsSignalAverage => {
+                        station.beacon_signal_average = Some(attr.as_i8()?);
+                    }
+                    StationInformationAttributes::ReceiveDuration => {
+                        station.rx_duration = Some(attr.as_u64()?);
+                    }
+                    StationInformationAttributes::TransmitDuration => {
+                        station.tx_duration = Some(attr.as_u64()?);
+                    }
+                    StationInformationAttributes::AcknowledgeSignal => {
+                        station.ack_signal = Some(attr.as_i8()?);
+                    }
+                    StationInformationAttributes::AcknowledgeSignalAverage => {
+                        station.ack_signal_average = Some(attr.as_i8()?);
+                    }
+                    StationInformationAttributes::ReceivedPacketsMpdus => {
+                        station.rx_mpdus = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::FrameCheckSequenceErrorCount => {
+                        station.fcs_error_count = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::AirtimeWeight => {
+                        station.airtime_weight = Some(attr.as_u16()?);
+                    }
+                    StationInformationAttributes::AirtimeLinkMetric => {
+                        station.airtime_link_metric = Some(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::AssociatedAtBootTime => {
+                        station.assoc_at_boottime = Some(attr.as_u64()?);
+                    }
+                    StationInformationAttributes::ConnectedToGate => {
+                        station.connected_to_gate = Some(attr.as_u8()? != 0);
+                    }
+                    StationInformationAttributes::ConnectedToAuthenticationServer => {
+                        station.connected_to_as = Some(attr.as_u8()? != 0);
+                    }
+                    StationInformationAttributes::BssParameter => {
+                        let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                        station.bss_param = Some(BssParam::from_attributes(&attrs)?);
+                    }
+                    StationInformationAttributes::StationFlags => {
+                        station.flags = Some(StationFlagUpdate::from_bytes(&attr.as_bytes())?);
+                    }
+                    StationInformationAttributes::TidStatistics => {
+                        let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                        for attr in attrs {
+                            // TID n is reported with identifier n + 1
+                            if attr.identifier == 0 {
+                                continue;
+                            }
+                            let tid = (attr.identifier - 1) as u8;
+                            let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                            station
+                                .tid_stats
+                                .push(TidStats::from_attributes(tid, &attrs)?);
+                        }
+                    }
+                    StationInformationAttributes::MeshLinkLinkId => {
+                        station.llid = Some(attr.as_u16()?);
+                    }
+                    StationInformationAttributes::MeshPeerLinkId => {
+                        station.plid = Some(attr.as_u16()?);
+                    }
+                    StationInformationAttributes::MeshPeerLinkState => {
+                        station.plink_state = PeerLinkState::convert_from(attr.as_u8()?);
+                    }
+                    StationInformationAttributes::LocalPowerMode => {
+                        station.local_pm = MeshPowerMode::convert_from(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::PeerPowerMode => {
+                        station.peer_pm = MeshPowerMode::convert_from(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::NonPeerPowerMode => {
+                        station.nonpeer_pm = MeshPowerMode::convert_from(attr.as_u32()?);
+                    }
+                    StationInformationAttributes::Invalid
+                    | StationInformationAttributes::Padding => {}
+                }
+            }
+        }
+        if rx_bytes64.is_some() {
+            station.rx_bytes = rx_bytes64;
+        }
+        if tx_bytes64.is_some() {
+            station.tx_bytes = tx_bytes64;
+        }
+        Ok(station)
+    }
+
+    /// Parse the station from a new station message
+    pub fn from_message(message: &generic::Message) -> Result<StationInfo, Error> {
+        let mut mac = None;
+        let mut attrs = None;
+        for attr in &message.attributes {
+            match Attribute::convert_from(attr.identifier) {
+                Some(Attribute::Mac) => {
+                    mac = Some(attr.as_hardware_address()?);
+                }
+                Some(Attribute::StaInfo) => {
+                    let (_, sta_attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                    attrs = Some(sta_attrs);
+                }
+                _ => (),
+            }
+        }
+        match (mac, attrs) {
+            (Some(mac), Some(attrs)) => StationInfo::from_attributes(mac, &attrs),
+            _ => Err(io::Error::new(io::ErrorKind::NotFound, "Station Not Found").into()),
+        }
+    }
+}
+
+impl fmt::Display for StationInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Station: {}", self.mac)?;
+        if let Some(signal) = self.signal {
+            write!(f, " Signal: {} dBm", signal)?;
+        }
+        if let Some(rx_bytes) = self.rx_bytes {
+            write!(f, " RX: {} bytes", rx_bytes)?;
+        }
+        if let Some(tx_bytes) = self.tx_bytes {
+            write!(f, " TX: {} bytes", tx_bytes)?;
+        }
+        if let Some(ref rate) = self.tx_bitrate {
+            write!(f, " TX rate: {}", rate)?;
+        }
+        if let Some(ref rate) = self.rx_bitrate {
+            write!(f, " RX rate: {}", rate)?;
+        }
+        if let Some(connected_time) = self.connected_time {
+            write!(f, " Connected: {} s", connected_time)?;
+        }
+        Ok(())
+    }
+}
+
+/// Station parameters for adding a station to an access point or changing it
+///
+/// Adding a station requires the supported rates, the listen interval and the AID.
+#[derive(Clone)]
+pub struct StationParams {
+    mac: HardwareAddress,
+    supported_rates: Vec<u8>,
+    listen_interval: Option<u16>,
+    aid: Option<u16>,
+    flags: StationFlagUpdate,
+    ht_capability: Option<Vec<u8>>,
+    vht_capability: Option<Vec<u8>>,
+    he_capability: Option<Vec<u8>>,
+    vlan: Option<u32>,
+    uapsd: Option<(u8, u8)>,
+}
+
+impl StationParams {
+    /// Parameters for the station with the given address
+    pub fn new(mac: HardwareAddress) -> StationParams {
+        StationParams {
+            mac,
+            supported_rates: vec![],
+            listen_interval: None,
+            aid: None,
+            flags: StationFlagUpdate::default(),
+            ht_capability: None,
+            vht_capability: None,
+            he_capability: None,
+            vlan: None,
+            uapsd: None,
+        }
+    }
+
+    /// Supported rates in units of 500 kbit/s
+    pub fn supported_rates(mut self, rates: &[u8]) -> StationParams {
+        self.supported_rates = rates.to_vec();
+        self
+    }
+
+    /// Listen interval in beacon intervals
+    pub fn listen_interval(mut self, interval: u16) -> StationParams {
+        self.listen_interval = Some(interval);
+        self
+    }
+
+    /// Association identifier
+    pub fn aid(mut self, aid: u16) -> StationParams {
+        self.aid = Some(aid);
+        self
+    }
+
+    /// Set or clear a station flag
+    pub fn flag(mut self, flag: StationFlags, value: bool) -> StationParams {
+        self.flags = self.flags.with(flag, value);
+        self
+    }
+
+    /// HT capabilities element body of the station
+    pub fn ht_capability(mut self, capability: &[u8]) -> StationParams {
+        self.ht_capability = Some(capability.to_vec());
+        self
+    }
+
+    /// VHT capabilities element body of the station
+    pub fn vht_capability(mut self, capability: &[u8]) -> StationParams {
+        self.vht_capability = Some(capability.to_vec());
+        self
+    }
+
+    /// HE capabilities element body of the station
+    pub fn he_capability(mut self, capability: &[u8]) -> StationParams {
+        self.he_capability = Some(capability.to_vec());
+        self
+    }
+
+    /// Move the station to the AP VLAN interface with the given interface index
+    pub fn vlan(mut self, interface_index: u32) -> StationParams {
+        self.vlan = Some(interface_index);
+        self
+    }
+
+    /// U-APSD enabled access categories as a bitmap and the maximum service period
+    pub fn uapsd(mut self, queues: u8, max_service_period: u8) -> StationParams {
+        self.uapsd = Some((queues, max_service_period));
+        self
+    }
+
+    /// Check the parameters against the capabilities of the phy
+    pub fn validate(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if self.supported_rates.len() > MAX_SUPPORTED_RATES {
+            return invalid("Too many supported rates");
+        }
+        if let Some(aid) = self.aid {
+            if aid == 0 || aid > MAX_AID {
+                return invalid("Invalid AID");
+            }
+        }
+        if let Some(ref capability) = self.ht_capability {
+            if capability.len() != HT_CAPABILITY_LENGTH {
+                return invalid("Invalid HT capability length");
+            }
+        }
+        if let Some(ref capability) = self.vht_capability {
+            if capability.len() != VHT_CAPABILITY_LENGTH {
+                return invalid("Invalid VHT capability length");
+            }
+        }
+        if let Some((queues, max_service_period)) = self.uapsd {
+            if queues > 0x0f || max_service_period > 3 {
+                return invalid("Invalid U-APSD parameters");
+            }
+        }
+        if self
+            .flags
+            .mask
+            .intersects(StationFlags::AUTHENTICATED | StationFlags::ASSOCIATED)
+            && !phy.features().contains(FeatureFlags::FULL_AP_CLIENT_STATE)
+        {
+            return invalid("Full AP client state not supported");
+        }
+        Ok(())
+    }
+
+    /// Check the parameters for adding a station
+    pub fn validate_new(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if self.supported_rates.is_empty() {
+            return invalid("No supported rates");
+        }
+        if self.listen_interval.is_none() {
+            return invalid("No listen interval");
+        }
+        if self.aid.is_none() {
+            return invalid("No AID");
+        }
+        self.validate(phy)
+    }
+
+    /// Append the parameters to a new station or set station message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        message.append_attribute(netlink::Attribute::new(Attribute::Mac, self.mac));
+        if !self.supported_rates.is_empty() {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::StaSupportedRates,
+                &self.supported_rates,
+            ));
+        }
+        if let Some(interval) = self.listen_interval {
+            message.append_attribute(netlink::Attribute::new(
+                Attribute::StaListenInterval,
+                interval,
+            ));
+        }
+        if let Some(aid) = self.aid {
+            message.append_attribute(netlink::Attribute::new(Attribute::StaAid, aid));
+        }
+        if !self.flags.mask.is_empty() {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::StaFlags2,
+                &self.flags.to_bytes(),
+            ));
+        }
+        if let Some(ref capability) = self.ht_capability {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::HtCapability,
+                capability,
+            ));
+        }
+        if let Some(ref capability) = self.vht_capability {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::VhtCapability,
+                capability,
+            ));
+        }
+        if let Some(ref capability) = self.he_capability {
+            message.append_attribute(netlink::Attribute::new_bytes(
+                Attribute::HighEfficiencyCapability,
+                capability,
+            ));
+        }
+        if let Some(interface_index) = self.vlan {
+            message.append_attribute(netlink::Attribute::new(Attribute::StaVlan, interface_index));
+        }
+        if let Some((queues, max_service_period)) = self.uapsd {
+            message.append_attribute(nested_attribute(
+                Attribute::StaWme,
+                &[
+                    netlink::Attribute::new(StationWmeAttribute::UapsdQueues, queues),
+                    netlink::Attribute::new(
+                        StationWmeAttribute::MaxServicePeriod,
+                        max_service_period,
+                    ),
+                ],
+            ));
+        }
+    }
+}
+
+/// Frame sent to a station when removing it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisconnectFrame {
+    Deauthentication(ReasonCode),
+    Disassociation(ReasonCode),
+}
+
+impl DisconnectFrame {
+    /// Append the management frame subtype and reason code to a delete station message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        let (subtype, reason) = match *self {
+            DisconnectFrame::Deauthentication(reason) => (SUBTYPE_DEAUTHENTICATION, reason),
+            DisconnectFrame::Disassociation(reason) => (SUBTYPE_DISASSOCIATION, reason),
+        };
+        message.append_attribute(netlink::Attribute::new(Attribute::MgmtSubtype, subtype));
+        message.append_attribute(netlink::Attribute::new(
+            Attribute::ReasonCode,
+            u16::from(reason),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+    use crate::unpack::LittleUnpack;
+
+    #[test]
+    fn test_parse_station_info() {
+        let bytes = [
+            8, 0, 1, 0, 100, 0, 0, 0, 8, 0, 2, 0, 0xe8, 0x03, 0, 0, 12, 0, 23, 0, 0, 0, 0, 0, 1, 0,
+            0, 0, 5, 0, 7, 0, 0xd8, 0, 0, 0, 12, 0, 8, 0, 6, 0, 1, 0, 0x1c, 0x02, 0, 0, 20, 0, 25,
+            0, 5, 0, 0, 0, 0xd6, 0, 0, 0, 5, 0, 1, 0, 0xda, 0, 0, 0,
+        ];
+        let (_, attrs) = netlink::Attribute::unpack_all(&bytes);
+        let mac = HardwareAddress::unpack_unchecked(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        let station = StationInfo::from_attributes(mac, &attrs).unwrap();
+        assert_eq!(station.mac, mac);
+        assert_eq!(station.inactive_time, Some(100));
+        assert_eq!(station.rx_bytes, Some(1 << 32));
+        assert_eq!(station.tx_bytes, None);
+        assert_eq!(station.signal, Some(-40));
+        assert_eq!(station.chain_signal, vec![-42, -38]);
+        let rate = station.tx_bitrate.unwrap();
+        assert_eq!(rate.bitrate(), Some(54_000));
+        assert_eq!(rate.width, RateWidth::Mhz20);
+    }
+
+    #[test]
+    fn test_parse_rate_info() {
+        let bytes = [
+            8, 0, 5, 0, 0xdb, 0x21, 0, 0, 5, 0, 6, 0, 9, 0, 0, 0, 5, 0, 7, 0, 2, 0, 0, 0, 4, 0, 8,
+            0, 4, 0, 4, 0,
+        ];
+        let (_, attrs) = netlink::Attribute::unpack_all(&bytes);
+        let rate = RateInfo::from_attributes(&attrs).unwrap();
+        assert_eq!(rate.bitrate(), Some(866_700));
+        assert_eq!(rate.vht_mcs, Some(9));
+        assert_eq!(rate.vht_nss, Some(2));
+        assert_eq!(rate.width, RateWidth::Mhz80);
+        assert!(rate.short_gi);
+        assert_eq!(
+            format!("{}", rate),
+            "866.7 MBit/s VHT-MCS 9 VHT-NSS 2 80MHz short GI"
+        );
+    }
+
+    #[test]
+    fn test_station_flag_update() {
+        let flags = StationFlagUpdate::default()
+            .with(StationFlags::AUTHORIZED, true)
+            .with(StationFlags::WME, false);
+        let data = flags.to_bytes();
+        assert_eq!(NativeEndian::read_u32(&data[0..4]), (1 << 1) | (1 << 3));
+        assert_eq!(NativeEndian::read_u32(&data[4..8]), 1 << 1);
+        assert_eq!(StationFlagUpdate::from_bytes(&data).unwrap(), flags);
+        assert_eq!(flags.get(StationFlags::WME), Some(false));
+        assert_eq!(flags.get(StationFlags::MFP), None);
+    }
+
+    #[test]
+    fn test_station_params() {
+        let mac = HardwareAddress::unpack_unchecked(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        let params = StationParams::new(mac)
+            .flag(StationFlags::AUTHORIZED, true)
+            .uapsd(0x0f, 2);
+        let mut message =
+            generic::Message::new(0, Command::SetStation, netlink::MessageMode::Acknowledge);
+        params.append_attributes(&mut message);
+        let attrs = &message.attributes;
+        assert_eq!(attrs.len(), 3);
+        assert_eq!(attrs[0].identifier, u16::from(Attribute::Mac));
+        assert_eq!(attrs[1].identifier, u16::from(Attribute::StaFlags2));
+        assert_eq!(attrs[1].as_bytes(), params.flags.to_bytes().to_vec());
+        assert_eq!(attrs[2].identifier, u16::from(Attribute::StaWme));
+        let (_, wme) = netlink::Attribute::unpack_all(&attrs[2].as_bytes());
+        assert_eq!(wme.len(), 2);
+        assert_eq!(
+            wme[0].identifier,
+            u16::from(StationWmeAttribute::UapsdQueues)
+        );
+        assert_eq!(wme[0].as_u8().unwrap(), 0x0f);
+        assert_eq!(
+            wme[1].identifier,
+            u16::from(StationWmeAttribute::MaxServicePeriod)
+        );
+        assert_eq!(wme[1].as_u8().unwrap(), 2);
+    }
+}
@@ -0,0 +1,216 @@
+//! ## Status and reason codes
+//!
+//! IEEE 802.11 status codes, reported in authentication and association responses, and reason
+//! codes, reported in deauthentication and disassociation frames.
+
+use std::fmt;
+
+/// Define a 802.11 code enumeration with conversions and a description of each code
+macro_rules! code_enumeration {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:expr, $description:expr;)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// Code without a known meaning
+            Other(u16),
+        }
+
+        impl From<u16> for $name {
+            fn from(v: u16) -> Self {
+                match v {
+                    $($value => $name::$variant,)*
+                    _ => $name::Other(v),
+                }
+            }
+        }
+
+        impl From<$name> for u16 {
+            fn from(v: $name) -> Self {
+                match v {
+                    $($name::$variant => $value,)*
+                    $name::Other(v) => v,
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match *self {
+                    $($name::$variant => write!(f, "{}", $description),)*
+                    $name::Other(v) => write!(f, "Unknown ({})", v),
+                }
+            }
+        }
+    };
+}
+
+code_enumeration! {
+    /// Status code
+    pub enum StatusCode {
+        Success = 0, "Successful";
+        UnspecifiedFailure = 1, "Unspecified failure";
+        CapabilitiesUnsupported = 10,
+            "Cannot support all requested capabilities in the capability information field";
+        ReassociationNoAssociation = 11,
+            "Reassociation denied due to inability to confirm that association exists";
+        AssociationDeniedUnspecified = 12, "Association denied due to reason outside the scope";
+        UnsupportedAuthenticationAlgorithm = 13,
+            "Responding station does not support the specified authentication algorithm";
+        UnknownAuthenticationTransaction = 14,
+            "Received an authentication frame with authentication transaction sequence number \
+             out of expected sequence";
+        ChallengeFailure = 15, "Authentication rejected because of challenge failure";
+        AuthenticationTimeout = 16,
+            "Authentication rejected due to timeout waiting for next frame in sequence";
+        ApUnableToHandleNewStation = 17,
+            "Association denied because AP is unable to handle additional associated stations";
+        BasicRatesMismatch = 18,
+            "Association denied due to requesting station not supporting all of the data \
+             rates in the BSSBasicRateSet parameter";
+        SpectrumManagementRequired = 22,
+            "Association request rejected because spectrum management capability is required";
+        PowerCapabilityNotValid = 23,
+            "Association request rejected because the information in the power capability \
+             element is unacceptable";
+        SupportedChannelsNotValid = 24,
+            "Association request rejected because the information in the supported channels \
+             element is unacceptable";
+        NoShortSlotTime = 25,
+            "Association denied due to requesting station not supporting short slot time";
+        NoHighThroughput = 27,
+            "Association denied because the requesting station does not support HT features";
+        R0KeyHolderUnreachable = 28, "R0KH unreachable";
+        AssociationRejectedTemporarily = 30,
+            "Association request rejected temporarily, try again later";
+        RobustManagementFramePolicyViolation = 31, "Robust management frame policy violation";
+        UnspecifiedQosFailure = 32, "Unspecified, QoS-related failure";
+        InsufficientBandwidth = 33,
+            "Association denied because QoS AP has insufficient bandwidth";
+        PoorChannelConditions = 34,
+            "Association denied due to excessive frame loss rates and/or poor conditions";
+        QosNotSupported = 35,
+            "Association denied because the requesting station does not support the QoS \
+             facility";
+        RequestDeclined = 37, "The request has been declined";
+        InvalidParameters = 38, "The request has not been successful as one or more \
+             parameters have invalid values";
+        InvalidElement = 40, "Invalid element";
+        InvalidGroupCipher = 41, "Invalid group cipher";
+        InvalidPairwiseCipher = 42, "Invalid pairwise cipher";
+        InvalidAkmp = 43, "Invalid AKMP";
+        UnsupportedRsnVersion = 44, "Unsupported RSNE version";
+        InvalidRsnCapabilities = 45, "Invalid RSNE capabilities";
+        CipherRejectedPerPolicy = 46, "Cipher suite rejected because of security policy";
+        ListenIntervalTooLarge = 51,
+            "Association denied because the listen interval is too large";
+        InvalidFtActionFrameCount = 52, "Invalid FT action frame count";
+        InvalidPmkid = 53, "Invalid pairwise master key identifier (PMKID)";
+        InvalidMde = 54, "Invalid MDE";
+        InvalidFte = 55, "Invalid FTE";
+        TryAnotherBss = 58, "Try another BSS";
+        InvalidRsne = 72, "Invalid contents of RSNE";
+        AntiCloggingTokenRequired = 76, "Anti-clogging token required";
+        FiniteCyclicGroupNotSupported = 77, "Finite cyclic group not supported";
+        TransmissionFailure = 79, "Transmission failure";
+        RejectedWithSuggestedBssTransition = 82, "Rejected with suggested BSS transition";
+        DeniedInsufficientMemory = 93,
+            "Association denied because the AP has insufficient memory";
+        DeniedVhtNotSupported = 104,
+            "Association denied because the requesting station does not support VHT features";
+        UnknownPasswordIdentifier = 123, "Unknown password identifier";
+        DeniedHeNotSupported = 124,
+            "Association denied because the requesting station does not support HE features";
+        SaeHashToElement = 126, "SAE hash-to-element";
+        SaePublicKey = 127, "SAE public key";
+    }
+}
+
+code_enumeration! {
+    /// Reason code
+    pub enum ReasonCode {
+        Unspecified = 1, "Unspecified reason";
+        PreviousAuthenticationNotValid = 2, "Previous authentication no longer valid";
+        DeauthenticationLeaving = 3,
+            "Deauthenticated because sending station is leaving (or has left) IBSS or ESS";
+        DisassociationDueToInactivity = 4, "Disassociated due to inactivity";
+        DisassociationApBusy = 5,
+            "Disassociated because AP is unable to handle all currently associated stations";
+        Class2FrameFromUnauthenticatedStation = 6,
+            "Class 2 frame received from nonauthenticated station";
+        Class3FrameFromUnassociatedStation = 7,
+            "Class 3 frame received from nonassociated station";
+        DisassociationStationHasLeft = 8,
+            "Disassociated because sending station is leaving (or has left) BSS";
+        StationRequestedAssociationWithoutAuthentication = 9,
+            "Station requesting (re)association is not authenticated with responding station";
+        PowerCapabilityNotValid = 10,
+            "Disassociated because the information in the power capability element is \
+             unacceptable";
+        SupportedChannelsNotValid = 11,
+            "Disassociated because the information in the supported channels element is \
+             unacceptable";
+        BssTransitionDisassociation = 12, "Disassociated due to BSS transition management";
+        InvalidElement = 13, "Invalid element";
+        MichaelMicFailure = 14, "Message integrity code (MIC) failure";
+        FourWayHandshakeTimeout = 15, "4-way handshake timeout";
+        GroupKeyHandshakeTimeout = 16, "Group key handshake timeout";
+        HandshakeElementMismatch = 17,
+            "Element in 4-way handshake different from (re)association request, probe \
+             response or beacon frame";
+        InvalidGroupCipher = 18, "Invalid group cipher";
+        InvalidPairwiseCipher = 19, "Invalid pairwise cipher";
+        InvalidAkmp = 20, "Invalid AKMP";
+        UnsupportedRsnVersion = 21, "Unsupported RSNE version";
+        InvalidRsnCapabilities = 22, "Invalid RSNE capabilities";
+        Ieee8021XAuthenticationFailed = 23, "IEEE 802.1X authentication failed";
+        CipherRejectedPerPolicy = 24, "Cipher suite rejected because of the security policy";
+        TdlsTeardownUnreachable = 25,
+            "TDLS direct-link teardown due to TDLS peer station unreachable via the TDLS \
+             direct link";
+        TdlsTeardownUnspecified = 26, "TDLS direct-link teardown for unspecified reason";
+        DisassociationLowAck = 34,
+            "Disassociated because excessive number of frames need to be acknowledged, but \
+             are not acknowledged";
+        ExceededTxop = 35, "Disassociated because station is transmitting outside the limits \
+             of its TXOPs";
+        StationLeaving = 36, "Requesting station is leaving the BSS (or resetting)";
+        EndTsBaDls = 37, "Requesting station is no longer using the stream or session";
+        UnknownTsBa = 38, "Requesting station received frames using a mechanism for which \
+             a setup has not been completed";
+        Timeout = 39, "Requested from peer station due to timeout";
+        PeerKeyMismatch = 45, "Peer station does not support the requested cipher suite";
+        InvalidFtActionFrameCount = 48, "Invalid FT action frame count";
+        InvalidPmkid = 49, "Invalid pairwise master key identifier (PMKID)";
+        InvalidMde = 50, "Invalid MDE";
+        InvalidFte = 51, "Invalid FTE";
+        MeshPeeringCancelled = 52, "Mesh peering cancelled";
+        MeshMaximumPeers = 53, "Mesh maximum number of peers reached";
+        MeshConfigurationPolicyViolation = 54, "Mesh configuration policy violation";
+        MeshCloseReceived = 55, "Mesh peering close received";
+        MeshMaximumRetries = 56, "Mesh peering maximum retries reached";
+        MeshConfirmTimeout = 57, "Mesh peering confirm timeout";
+        MeshInvalidGtk = 58, "Mesh invalid GTK";
+        MeshInconsistentParameters = 59, "Mesh inconsistent parameters";
+        MeshInvalidSecurityCapability = 60, "Mesh invalid security capability";
+        MeshPathErrorNoProxyInformation = 61, "Mesh path error, no proxy information";
+        MeshPathErrorNoForwardingInformation = 62, "Mesh path error, no forwarding information";
+        MeshPathErrorDestinationUnreachable = 63, "Mesh path error, destination unreachable";
+        MacAddressAlreadyExistsInMbss = 64, "MAC address already exists in the mesh BSS";
+        MeshChannelSwitchRegulatoryRequirements = 65,
+            "Mesh channel switch due to regulatory requirements";
+        MeshChannelSwitchUnspecified = 66, "Mesh channel switch for unspecified reason";
+    }
+}
+
+impl StatusCode {
+    /// The status code reports success
+    pub fn is_success(self) -> bool {
+        self == StatusCode::Success
+    }
+}
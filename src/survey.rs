@@ -0,0 +1,171 @@
+//! ## Survey
+//!
+//! Channel survey results, noise and channel occupancy per frequency.
+
+use std::fmt;
+use std::io;
+
+use crate::attributes::{Attribute, SurveyInfoAttribute};
+use netlink_rust as netlink;
+use netlink_rust::generic;
+use netlink_rust::{ConvertFrom, Error};
+
+/// Survey information for a single frequency
+///
+/// The times are cumulative milliseconds since the survey data was reset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurveyInfo {
+    /// Frequency in MHz
+    pub frequency: u32,
+    /// Frequency offset in kHz
+    pub frequency_offset: Option<u32>,
+    /// Noise level in dBm
+    pub noise: Option<i8>,
+    /// The frequency is currently in use by the device
+    pub in_use: bool,
+    /// Time the radio was on the channel
+    pub time: Option<u64>,
+    /// Time the primary channel was sensed busy
+    pub time_busy: Option<u64>,
+    /// Time the extension channel was sensed busy
+    pub time_ext_busy: Option<u64>,
+    /// Time spent receiving
+    pub time_rx: Option<u64>,
+    /// Time spent transmitting
+    pub time_tx: Option<u64>,
+    /// Time spent on the channel while scanning
+    pub time_scan: Option<u64>,
+    /// Time spent receiving frames destined to the local BSS
+    pub time_bss_rx: Option<u64>,
+}
+
+impl SurveyInfo {
+    /// Parse the nested survey information attributes
+    pub fn from_attributes(attributes: &[netlink::Attribute]) -> Result<SurveyInfo, Error> {
+        let mut frequency = None;
+        let mut frequency_offset = None;
+        let mut noise = None;
+        let mut in_use = false;
+        let mut time = None;
+        let mut time_busy = None;
+        let mut time_ext_busy = None;
+        let mut time_rx = None;
+        let mut time_tx = None;
+        let mut time_scan = None;
+        let mut time_bss_rx = None;
+        for attr in attributes {
+            let identifier = SurveyInfoAttribute::convert_from(attr.identifier);
+            if let Some(identifier) = identifier {
+                match identifier {
+                    SurveyInfoAttribute::Frequency => {
+                        frequency = Some(attr.as_u32()?);
+                    }
+                    SurveyInfoAttribute::FrequencyOffset => {
+                        frequency_offset = Some(attr.as_u32()?);
+                    }
+                    SurveyInfoAttribute::Noise => {
+                        noise = Some(attr.as_i8()?);
+                    }
+                    SurveyInfoAttribute::InUse => {
+                        in_use = true;
+                    }
+                    SurveyInfoAttribute::Time => {
+                        time = Some(attr.as_u64()?);
+                    }
+                    SurveyInfoAttribute::TimeBusy => {
+                        time_busy = Some(attr.as_u64()?);
+                    }
+                    SurveyInfoAttribute::TimeExtensionBusy => {
+                        time_ext_busy = Some(attr.as_u64()?);
+                    }
+                    SurveyInfoAttribute::TimeReceive => {
+                        time_rx = Some(attr.as_u64()?);
+                    }
+                    SurveyInfoAttribute::TimeTransmit => {
+                        time_tx = Some(attr.as_u64()?);
+                    }
+                    SurveyInfoAttribute::TimeScan => {
+                        time_scan = Some(attr.as_u64()?);
+                    }
+                    SurveyInfoAttribute::TimeBssReceive => {
+                        time_bss_rx = Some(attr.as_u64()?);
+                    }
+                    SurveyInfoAttribute::Invalid | SurveyInfoAttribute::Padding => (),
+                }
+            }
+        }
+        match frequency {
+            Some(frequency) => Ok(SurveyInfo {
+                frequency,
+                frequency_offset,
+                noise,
+                in_use,
+                time,
+                time_busy,
+                time_ext_busy,
+                time_rx,
+                time_tx,
+                time_scan,
+                time_bss_rx,
+            }),
+            None => Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid survey").into()),
+        }
+    }
+
+    /// Parse the survey information from a survey result message
+    pub fn from_message(message: &generic::Message) -> Result<SurveyInfo, Error> {
+        for attr in &message.attributes {
+            if let Some(Attribute::SurveyInfo) = Attribute::convert_from(attr.identifier) {
+                let (_, attrs) = netlink::Attribute::unpack_all(&attr.as_bytes());
+                return SurveyInfo::from_attributes(&attrs);
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::NotFound, "Survey Not Found").into())
+    }
+
+    /// Percentage of the time the channel was busy
+    pub fn utilisation(&self) -> Option<f64> {
+        match (self.time, self.time_busy) {
+            (Some(time), Some(busy)) if time > 0 => Some(busy as f64 * 100.0 / time as f64),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SurveyInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Frequency: {} MHz", self.frequency)?;
+        if self.in_use {
+            write!(f, " [in use]")?;
+        }
+        if let Some(noise) = self.noise {
+            write!(f, " Noise: {} dBm", noise)?;
+        }
+        if let Some(utilisation) = self.utilisation() {
+            write!(f, " Utilisation: {:.1} %", utilisation)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_survey_info() {
+        let bytes = [
+            8, 0, 1, 0, 0x6c, 0x09, 0, 0, 5, 0, 2, 0, 0xa1, 0, 0, 0, 4, 0, 3, 0, 12, 0, 4, 0, 0xe8,
+            0x03, 0, 0, 0, 0, 0, 0, 12, 0, 5, 0, 250, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let (_, attrs) = netlink::Attribute::unpack_all(&bytes);
+        let survey = SurveyInfo::from_attributes(&attrs).unwrap();
+        assert_eq!(survey.frequency, 2412);
+        assert_eq!(survey.noise, Some(-95));
+        assert!(survey.in_use);
+        assert_eq!(survey.time, Some(1000));
+        assert_eq!(survey.time_busy, Some(250));
+        assert_eq!(survey.utilisation(), Some(25.0));
+        assert!(SurveyInfo::from_attributes(&attrs[1..]).is_err());
+    }
+}
@@ -1,10 +1,281 @@
+use crate::ap::{BeaconConfig, BssParameters};
 use crate::attributes;
+use crate::bss::Bss;
+use crate::chandef::{Chandef, ChannelSwitchEvent};
 use crate::commands::Command;
+use crate::connect::{ConnectParams, ConnectResult, ConnectionEvent};
+use crate::ibss::IbssParams;
+use crate::mesh::{MeshConfig, MeshPath, MeshSetup};
+use crate::mlme::{AssociateParams, AuthenticateParams, MlmeEvent};
 use netlink_rust::generic;
-use netlink_rust::{Attribute, ConvertFrom, Error, HardwareAddress, MessageMode, Socket};
+use netlink_rust::{Attribute, ConvertFrom, Error, HardwareAddress, MessageMode, Protocol, Socket};
+use crate::pack::{flag_attribute, nested_attribute};
 use crate::regulatory::RegulatoryInformation;
+use crate::scan::{ScanRequest, ScheduledScanEvent, ScheduledScanRequest};
+use crate::station::{DisconnectFrame, StationInfo, StationParams};
+use crate::status::ReasonCode;
+use crate::survey::SurveyInfo;
+use crate::wireless_phy::{ExtendedFeaturesFlags, FeatureFlags, WirelessPhy};
 use std::fmt;
 use std::io;
+use std::os::unix::io::AsRawFd;
+use std::time::{Duration, Instant};
+
+/// Set on messages of a dump which was interrupted by a change of the dumped data
+const NLM_F_DUMP_INTR: u16 = 0x10;
+/// Number of times an interrupted dump is restarted before giving up
+const MAX_DUMP_RESTARTS: usize = 5;
+/// Maximum length of an interface name, without the terminating nul
+const MAX_INTERFACE_NAME_LENGTH: usize = 15;
+const MAX_MESH_ID_LENGTH: usize = 32;
+/// Length of the VHT MU-MIMO group membership and user position arrays
+const MU_MIMO_GROUP_DATA_LENGTH: usize = 24;
+/// Highest monitor flag of nl80211
+const MAX_MONITOR_FLAG: u32 = 6;
+
+/// Wait until the socket has data to read
+///
+/// Fails with `TimedOut` if the deadline passes before any data arrives.
+fn wait_readable(socket: &Socket, deadline: Option<Instant>) -> Result<(), Error> {
+    let timeout = match deadline {
+        Some(deadline) => {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "Timed out").into());
+            }
+            let remaining = (deadline - now).as_millis() + 1;
+            remaining.min(libc::c_int::MAX as u128) as libc::c_int
+        }
+        None => -1,
+    };
+    let mut fds = libc::pollfd {
+        fd: socket.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let result = unsafe { libc::poll(&mut fds, 1, timeout) };
+    if result < 0 {
+        let error = io::Error::last_os_error();
+        if error.kind() == io::ErrorKind::Interrupted {
+            return Ok(());
+        }
+        return Err(error.into());
+    }
+    if result == 0 {
+        return Err(io::Error::new(io::ErrorKind::TimedOut, "Timed out").into());
+    }
+    Ok(())
+}
+
+bitflags! {
+    /// Monitor interface flags, bit n corresponds to monitor flag n of nl80211
+    pub struct MonitorFlags: u32 {
+        /// Pass frames with a bad FCS
+        const FCS_FAIL    = 1 << 1;
+        /// Pass frames with a bad PLCP header
+        const PLCP_FAIL   = 1 << 2;
+        /// Pass control frames
+        const CONTROL     = 1 << 3;
+        /// Pass frames of other BSSs, disabling the BSSID filter
+        const OTHER_BSS   = 1 << 4;
+        /// Report frames after processing, the other flags are ignored
+        const COOK_FRAMES = 1 << 5;
+        /// Acknowledge unicast frames addressed to the interface
+        const ACTIVE      = 1 << 6;
+    }
+}
+
+impl MonitorFlags {
+    /// Monitor flags as a nested attribute with a flag attribute per set flag
+    fn to_attribute(self) -> Attribute {
+        let flags: Vec<Attribute> = (1..=MAX_MONITOR_FLAG)
+            .filter(|flag| self.bits() & (1 << flag) != 0)
+            .map(|flag| flag_attribute(flag as u16))
+            .collect();
+        nested_attribute(attributes::Attribute::MntrFlags, &flags)
+    }
+
+    /// Check the flags against the capabilities of the phy
+    fn validate(self, phy: &WirelessPhy) -> Result<(), Error> {
+        if self.contains(MonitorFlags::ACTIVE)
+            && !phy.features().contains(FeatureFlags::ACTIVE_MONITOR)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Active monitor not supported",
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Check that the phy can follow MU-MIMO groups or a station in monitor mode
+fn validate_mu_mimo_sniffer(phy: &WirelessPhy, group_data: Option<&[u8]>) -> Result<(), Error> {
+    let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+    if !phy
+        .extended_features()
+        .contains(ExtendedFeaturesFlags::MU_MIMO_AIR_SNIFFER)
+    {
+        return invalid("MU-MIMO air sniffer not supported");
+    }
+    if matches!(group_data, Some(data) if data.len() != MU_MIMO_GROUP_DATA_LENGTH) {
+        return invalid("Invalid MU-MIMO group data length");
+    }
+    Ok(())
+}
+
+/// Options for creating a virtual interface
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterfaceOptions {
+    mac: Option<HardwareAddress>,
+    four_address: Option<bool>,
+    mesh_id: Option<Vec<u8>>,
+    socket_owner: bool,
+    monitor_flags: Option<MonitorFlags>,
+    mu_mimo_group_data: Option<Vec<u8>>,
+    mu_mimo_follow_mac: Option<HardwareAddress>,
+}
+
+impl InterfaceOptions {
+    pub fn new() -> InterfaceOptions {
+        InterfaceOptions::default()
+    }
+
+    /// Create the interface with the given MAC address instead of the address of the phy
+    pub fn mac(mut self, mac: HardwareAddress) -> InterfaceOptions {
+        self.mac = Some(mac);
+        self
+    }
+
+    /// Use 4-address frames, for station and AP VLAN interfaces
+    pub fn four_address(mut self, enable: bool) -> InterfaceOptions {
+        self.four_address = Some(enable);
+        self
+    }
+
+    /// Mesh ID of a mesh point interface
+    pub fn mesh_id(mut self, mesh_id: &[u8]) -> InterfaceOptions {
+        self.mesh_id = Some(mesh_id.to_vec());
+        self
+    }
+
+    /// Delete the interface when the socket creating it is closed
+    pub fn socket_owner(mut self) -> InterfaceOptions {
+        self.socket_owner = true;
+        self
+    }
+
+    /// Flags of a monitor interface
+    pub fn monitor_flags(mut self, flags: MonitorFlags) -> InterfaceOptions {
+        self.monitor_flags = Some(flags);
+        self
+    }
+
+    /// Capture the frames of the MU-MIMO groups, given as the VHT group membership and user
+    /// position arrays
+    pub fn mu_mimo_group_data(mut self, data: &[u8]) -> InterfaceOptions {
+        self.mu_mimo_group_data = Some(data.to_vec());
+        self
+    }
+
+    /// Capture the MU-MIMO frames of the station with the MAC address
+    pub fn mu_mimo_follow_mac(mut self, mac: HardwareAddress) -> InterfaceOptions {
+        self.mu_mimo_follow_mac = Some(mac);
+        self
+    }
+
+    /// Check the name, type and options of a new interface against the capabilities of the phy
+    pub fn validate(
+        &self,
+        phy: &WirelessPhy,
+        name: &str,
+        interface_type: &attributes::InterfaceType,
+    ) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if name.is_empty() || name.len() > MAX_INTERFACE_NAME_LENGTH {
+            return invalid("Invalid interface name");
+        }
+        if !phy.supports_interface_type(interface_type.clone()) {
+            return invalid("Interface type not supported");
+        }
+        // Devices without a network interface always take the given address
+        let wdev_only = matches!(
+            *interface_type,
+            attributes::InterfaceType::P2pDevice | attributes::InterfaceType::Nan
+        );
+        if self.mac.is_some() && !wdev_only && !phy.features().contains(FeatureFlags::MAC_ON_CREATE)
+        {
+            return invalid("MAC address on interface creation not supported");
+        }
+        if self.four_address.is_some() && !supports_four_address(interface_type) {
+            return invalid("4-address mode not supported by the interface type");
+        }
+        if let Some(ref mesh_id) = self.mesh_id {
+            if *interface_type != attributes::InterfaceType::MeshPoint {
+                return invalid("Mesh ID only valid for mesh point interfaces");
+            }
+            if mesh_id.len() > MAX_MESH_ID_LENGTH {
+                return invalid("Invalid mesh ID");
+            }
+        }
+        let mu_mimo = self.mu_mimo_group_data.is_some() || self.mu_mimo_follow_mac.is_some();
+        if (self.monitor_flags.is_some() || mu_mimo)
+            && *interface_type != attributes::InterfaceType::Monitor
+        {
+            return invalid("Monitor options only valid for monitor interfaces");
+        }
+        if let Some(flags) = self.monitor_flags {
+            flags.validate(phy)?;
+        }
+        if mu_mimo {
+            validate_mu_mimo_sniffer(phy, self.mu_mimo_group_data.as_deref())?;
+        }
+        Ok(())
+    }
+
+    /// Append the options to a new interface message
+    pub fn append_attributes(&self, message: &mut generic::Message) {
+        if let Some(mac) = self.mac {
+            message.append_attribute(Attribute::new(attributes::Attribute::Mac, mac));
+        }
+        if let Some(enable) = self.four_address {
+            message.append_attribute(Attribute::new(
+                attributes::Attribute::FourAddr,
+                enable as u8,
+            ));
+        }
+        if let Some(ref mesh_id) = self.mesh_id {
+            message.append_attribute(Attribute::new_bytes(attributes::Attribute::MeshId, mesh_id));
+        }
+        if self.socket_owner {
+            message.append_attribute(flag_attribute(attributes::Attribute::SocketOwner));
+        }
+        if let Some(flags) = self.monitor_flags {
+            message.append_attribute(flags.to_attribute());
+        }
+        if let Some(ref data) = self.mu_mimo_group_data {
+            message.append_attribute(Attribute::new_bytes(
+                attributes::Attribute::MuMimoGroupData,
+                data,
+            ));
+        }
+        if let Some(mac) = self.mu_mimo_follow_mac {
+            message.append_attribute(Attribute::new(
+                attributes::Attribute::MuMimoFollowMacAddr,
+                mac,
+            ));
+        }
+    }
+}
+
+/// 4-address mode is only used by stations and AP VLANs
+fn supports_four_address(interface_type: &attributes::InterfaceType) -> bool {
+    matches!(
+        *interface_type,
+        attributes::InterfaceType::Station | attributes::InterfaceType::ApVlan
+    )
+}
 
 #[derive(PartialEq)]
 pub enum WirelessDeviceId {
@@ -26,8 +297,10 @@ impl fmt::Display for WirelessDeviceId {
 pub struct WirelessInterface {
     pub family: generic::Family,
     pub phy_id: u32,
-    pub interface_name: String,
-    pub interface_index: u32,
+    /// Name of the network interface, `None` for wireless devices without a network interface
+    pub interface_name: Option<String>,
+    /// Index of the network interface, `None` for wireless devices without a network interface
+    pub interface_index: Option<u32>,
     pub device_id: Option<u64>,
     pub mac: HardwareAddress,
     pub interface_type: attributes::InterfaceType,
@@ -39,10 +312,16 @@ pub struct WirelessInterface {
 
 impl fmt::Display for WirelessInterface {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref name) = self.interface_name {
+            write!(f, "Name: {} ", name)?;
+        }
+        if let Some(index) = self.interface_index {
+            write!(f, "Index: {} ", index)?;
+        }
         write!(
             f,
-            "Name: {} Index: {} Address: {} Interface Type: \"{:?}\"",
-            self.interface_name, self.interface_index, self.mac, self.interface_type
+            "Address: {} Interface Type: \"{:?}\"",
+            self.mac, self.interface_type
         )?;
         if let Some(id) = self.device_id {
             write!(f, " Wireless Device Id: {}", id)?;
@@ -160,16 +439,13 @@ impl WirelessInterface {
         if let Some(id) = device_id {
             wdev_id = WirelessDeviceId::DeviceIdentifier(id);
         }
-        if phy_id.is_some()
-            && interface_name.is_some()
-            && interface_index.is_some()
-            && mac.is_some()
-        {
+        // Wireless devices such as P2P devices have no network interface, only a device id
+        if phy_id.is_some() && wdev_id != WirelessDeviceId::None && mac.is_some() {
             Ok(WirelessInterface {
                 family,
                 phy_id: phy_id.unwrap(),
-                interface_name: interface_name.unwrap(),
-                interface_index: interface_index.unwrap(),
+                interface_name,
+                interface_index,
                 device_id,
                 mac: mac.unwrap(),
                 interface_type,
@@ -213,8 +489,225 @@ impl WirelessInterface {
         Ok(tx_msg)
     }
 
+    /// Send a dump request and collect the replies
+    ///
+    /// The dump is restarted if the kernel reports that it was interrupted, failing with
+    /// an error if it is still interrupted after `MAX_DUMP_RESTARTS` restarts.
+    fn dump_messages(
+        &self,
+        socket: &mut Socket,
+        tx_msg: &generic::Message,
+    ) -> Result<Vec<generic::Message>, Error> {
+        for _ in 0..=MAX_DUMP_RESTARTS {
+            socket.send_message(tx_msg)?;
+            let mut interrupted = false;
+            let mut rx_messages = vec![];
+            loop {
+                let messages = socket.receive_messages()?;
+                if messages.is_empty() {
+                    break;
+                }
+                for m in messages {
+                    if m.header.flags & NLM_F_DUMP_INTR == NLM_F_DUMP_INTR {
+                        interrupted = true;
+                    }
+                    if m.header.identifier == self.family.id {
+                        let (_, msg) = generic::Message::unpack(&m.data)?;
+                        rx_messages.push(msg);
+                    }
+                }
+            }
+            if !interrupted {
+                return Ok(rx_messages);
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::Other, "Dump interrupted repeatedly").into())
+    }
+
+    /// Delete the virtual interface
+    pub fn delete(&self, socket: &mut Socket) -> Result<(), Error> {
+        socket.send_message(
+            &self.prepare_message(Command::DelInterface, MessageMode::Acknowledge)?,
+        )?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a set interface message with the attribute and wait for the acknowledgement
+    fn set_interface(&self, socket: &mut Socket, attribute: Attribute) -> Result<(), Error> {
+        let mut tx_msg = self.prepare_message(Command::SetInterface, MessageMode::Acknowledge)?;
+        tx_msg.append_attribute(attribute);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Change the type of the interface, usually the interface must be down
+    ///
+    /// `phy` is the phy of the interface, the type must be supported by it.
+    pub fn set_type(
+        &mut self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        interface_type: attributes::InterfaceType,
+    ) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if phy.identifier() != self.phy_id {
+            return invalid("Not the phy of the interface");
+        }
+        if !phy.supports_interface_type(interface_type.clone()) {
+            return invalid("Interface type not supported");
+        }
+        self.set_interface(
+            socket,
+            Attribute::new(
+                attributes::Attribute::Iftype,
+                u32::from(interface_type.clone()),
+            ),
+        )?;
+        self.interface_type = interface_type;
+        Ok(())
+    }
+
+    /// Enable or disable 4-address mode of a station or AP VLAN interface
+    pub fn set_4addr(&self, socket: &mut Socket, enable: bool) -> Result<(), Error> {
+        if !supports_four_address(&self.interface_type) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "4-address mode not supported by the interface type",
+            )
+            .into());
+        }
+        self.set_interface(
+            socket,
+            Attribute::new(attributes::Attribute::FourAddr, enable as u8),
+        )
+    }
+
+    /// Send a command to a P2P device and wait for the acknowledgement
+    fn p2p_device_command(&self, socket: &mut Socket, command: Command) -> Result<(), Error> {
+        if self.interface_type != attributes::InterfaceType::P2pDevice {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Not a P2P device").into());
+        }
+        socket.send_message(&self.prepare_message(command, MessageMode::Acknowledge)?)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start a P2P device, making it available for P2P discovery and group formation
+    pub fn start_p2p_device(&self, socket: &mut Socket) -> Result<(), Error> {
+        self.p2p_device_command(socket, Command::StartP2pDevice)
+    }
+
+    /// Stop a P2P device
+    pub fn stop_p2p_device(&self, socket: &mut Socket) -> Result<(), Error> {
+        self.p2p_device_command(socket, Command::StopP2pDevice)
+    }
+
+    /// Check that the interface is a monitor interface of the phy
+    fn validate_monitor(&self, phy: &WirelessPhy) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if phy.identifier() != self.phy_id {
+            return invalid("Not the phy of the interface");
+        }
+        if self.interface_type != attributes::InterfaceType::Monitor {
+            return invalid("Not a monitor interface");
+        }
+        Ok(())
+    }
+
+    /// Change the flags of a monitor interface, `phy` is the phy of the interface
+    pub fn set_monitor_flags(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        flags: MonitorFlags,
+    ) -> Result<(), Error> {
+        self.validate_monitor(phy)?;
+        flags.validate(phy)?;
+        self.set_interface(socket, flags.to_attribute())
+    }
+
+    /// Capture the frames of the MU-MIMO groups on a monitor interface
+    ///
+    /// `data` is the VHT group membership array followed by the user position array.
+    pub fn set_mu_mimo_group_data(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.validate_monitor(phy)?;
+        validate_mu_mimo_sniffer(phy, Some(data))?;
+        self.set_interface(
+            socket,
+            Attribute::new_bytes(attributes::Attribute::MuMimoGroupData, data),
+        )
+    }
+
+    /// Capture the MU-MIMO frames of the station with the MAC address on a monitor interface
+    pub fn set_mu_mimo_follow_mac(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        mac: HardwareAddress,
+    ) -> Result<(), Error> {
+        self.validate_monitor(phy)?;
+        validate_mu_mimo_sniffer(phy, None)?;
+        self.set_interface(
+            socket,
+            Attribute::new(attributes::Attribute::MuMimoFollowMacAddr, mac),
+        )
+    }
+
+    /// Get the scan results cached by the kernel
+    pub fn get_scan_results(&self, socket: &mut Socket) -> Result<Vec<Bss>, Error> {
+        let msg = self.prepare_message(Command::GetScan, MessageMode::Dump)?;
+        let mut results = vec![];
+        for message in self.dump_messages(socket, &msg)? {
+            if message.command == Command::NewScanResults {
+                results.push(Bss::from_message(&message)?);
+            }
+        }
+        Ok(results)
+    }
+
     pub fn trigger_scan(&self, socket: &mut Socket) -> Result<(), Error> {
-        let msg = self.prepare_message(Command::TriggerScan, MessageMode::Acknowledge)?;
+        self.send_scan_request(socket, &ScanRequest::new())
+    }
+
+    /// Trigger a scan using the provided scan request parameters
+    ///
+    /// The request is checked against the capabilities of the phy before it is sent.
+    pub fn trigger_scan_with(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        request: &ScanRequest,
+    ) -> Result<(), Error> {
+        request.validate(phy)?;
+        self.send_scan_request(socket, request)
+    }
+
+    /// Send a trigger scan message and wait for the acknowledgement
+    fn send_scan_request(&self, socket: &mut Socket, request: &ScanRequest) -> Result<(), Error> {
+        let mut msg = self.prepare_message(Command::TriggerScan, MessageMode::Acknowledge)?;
+        request.append_attributes(&mut msg);
         socket.send_message(&msg)?;
         loop {
             let messages = socket.receive_messages()?;
@@ -225,6 +718,94 @@ impl WirelessInterface {
         Ok(())
     }
 
+    /// Check if the message refers to this interface
+    fn is_message_for_device(&self, message: &generic::Message) -> bool {
+        message.attributes.iter().any(|attr| {
+            match attributes::Attribute::convert_from(attr.identifier) {
+                Some(attributes::Attribute::Ifindex) => {
+                    self.interface_index.is_some() && attr.as_u32().ok() == self.interface_index
+                }
+                Some(attributes::Attribute::Wdev) => {
+                    self.device_id.is_some() && attr.as_u64().ok() == self.device_id
+                }
+                _ => false,
+            }
+        })
+    }
+
+    /// Open a socket subscribed to the named nl80211 multicast group
+    fn subscribe(&self, group_name: &str) -> Result<Socket, Error> {
+        let group = self
+            .family
+            .multicast_groups
+            .iter()
+            .find(|group| group.name == group_name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Multicast Group Not Found"))?;
+        let mut socket = Socket::new(Protocol::Generic)?;
+        socket.multicast_group_subscribe(group.id)?;
+        Ok(socket)
+    }
+
+    /// Wait for and receive the next batch of events concerning this interface
+    fn receive_device_events(
+        &self,
+        event_socket: &mut Socket,
+        deadline: Option<Instant>,
+    ) -> Result<Vec<generic::Message>, Error> {
+        wait_readable(event_socket, deadline)?;
+        let mut events = vec![];
+        for m in event_socket.receive_messages()? {
+            if m.header.identifier != self.family.id {
+                continue;
+            }
+            let (_, msg) = generic::Message::unpack(&m.data)?;
+            if self.is_message_for_device(&msg) {
+                events.push(msg);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Open a socket receiving the scan events of the nl80211 family
+    pub fn scan_event_socket(&self) -> Result<Socket, Error> {
+        self.subscribe("scan")
+    }
+
+    /// Trigger a scan and wait for it to complete
+    ///
+    /// The request is checked against the capabilities of the phy before it is sent. Returns the
+    /// scan results when the scan has finished. An aborted scan results in a `ConnectionAborted`
+    /// error and a scan not finished within `timeout` results in a `TimedOut` error.
+    pub fn scan_and_wait(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        request: &ScanRequest,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Bss>, Error> {
+        request.validate(phy)?;
+        let mut event_socket = self.subscribe("scan")?;
+        self.send_scan_request(socket, request)?;
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            for msg in self.receive_device_events(&mut event_socket, deadline)? {
+                match Command::convert_from(msg.command) {
+                    Some(Command::NewScanResults) => {
+                        return self.get_scan_results(socket);
+                    }
+                    Some(Command::ScanAborted) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::ConnectionAborted,
+                            "Scan aborted",
+                        )
+                        .into());
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
     pub fn abort_scan(&self, socket: &mut Socket) -> Result<(), Error> {
         let msg = self.prepare_message(Command::AbortScan, MessageMode::Acknowledge)?;
         socket.send_message(&msg)?;
@@ -266,21 +847,104 @@ impl WirelessInterface {
         Ok(())
     }
 
-    pub fn get_survey(&self, socket: &mut Socket) -> Result<(), Error> {
-        println!("Get Survey...");
-        let msg = self.prepare_message(Command::GetSurvey, MessageMode::Dump)?;
+    /// Start a scheduled scan
+    ///
+    /// The request is checked against the capabilities of the phy before it is sent. Returns the
+    /// cookie identifying the scheduled scan if the request was started with `multi`, the cookie
+    /// is needed to stop the scan. A start event not received within `timeout` results in a
+    /// `TimedOut` error.
+    pub fn start_scheduled_scan(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        request: &ScheduledScanRequest,
+        timeout: Option<Duration>,
+    ) -> Result<Option<u64>, Error> {
+        request.validate(phy)?;
+        // The cookie is only reported through the start event
+        let mut event_socket = if request.is_multi() {
+            Some(self.scan_event_socket()?)
+        } else {
+            None
+        };
+        let mut msg =
+            self.prepare_message(Command::StartScheduledScan, MessageMode::Acknowledge)?;
+        request.append_attributes(&mut msg);
+        socket.send_message(&msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        if let Some(ref mut event_socket) = event_socket {
+            let deadline = timeout.map(|timeout| Instant::now() + timeout);
+            loop {
+                for msg in self.receive_device_events(event_socket, deadline)? {
+                    if let Some(ScheduledScanEvent::Started(cookie)) =
+                        ScheduledScanEvent::from_message(&msg)
+                    {
+                        return Ok(cookie);
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Stop a scheduled scan, `cookie` identifies a scan started with `multi`
+    pub fn stop_scheduled_scan(
+        &self,
+        socket: &mut Socket,
+        cookie: Option<u64>,
+    ) -> Result<(), Error> {
+        let mut msg = self.prepare_message(Command::StopScheduledScan, MessageMode::Acknowledge)?;
+        if let Some(cookie) = cookie {
+            msg.append_attribute(Attribute::new(attributes::Attribute::Cookie, cookie));
+        }
         socket.send_message(&msg)?;
         loop {
             let messages = socket.receive_messages()?;
             if messages.is_empty() {
-                println!("Empty");
                 break;
             }
-            println!("Messages {}", messages.len());
         }
         Ok(())
     }
 
+    /// Wait for scheduled scan events on a socket opened with `scan_event_socket`
+    ///
+    /// Fails with `TimedOut` if no event for this interface arrives within `timeout`.
+    pub fn wait_scheduled_scan_events(
+        &self,
+        event_socket: &mut Socket,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<ScheduledScanEvent>, Error> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let events: Vec<ScheduledScanEvent> = self
+                .receive_device_events(event_socket, deadline)?
+                .iter()
+                .filter_map(ScheduledScanEvent::from_message)
+                .collect();
+            if !events.is_empty() {
+                return Ok(events);
+            }
+        }
+    }
+
+    /// Get the channel survey results of the interface
+    pub fn get_survey(&self, socket: &mut Socket) -> Result<Vec<SurveyInfo>, Error> {
+        let msg = self.prepare_message(Command::GetSurvey, MessageMode::Dump)?;
+        let mut results = vec![];
+        for message in self.dump_messages(socket, &msg)? {
+            if message.command == Command::NewSurveyResults {
+                results.push(SurveyInfo::from_message(&message)?);
+            }
+        }
+        Ok(results)
+    }
+
     pub fn disconnect(&self, socket: &mut Socket) -> Result<(), Error> {
         socket.send_message(
             &self.prepare_device_message(Command::Disconnect, MessageMode::Acknowledge)?,
@@ -294,12 +958,378 @@ impl WirelessInterface {
         Ok(())
     }
 
-    pub fn connect(&self, socket: &mut Socket, ssid: &str, _: &str) -> Result<(), Error> {
-        let mut tx_msg = self.prepare_device_message(Command::Connect, MessageMode::Acknowledge)?;
-        tx_msg.append_attribute(Attribute::new_string_with_nul(
-            attributes::Attribute::Ssid,
-            ssid,
+    /// Connect to a BSS, the result is reported with a connect event
+    ///
+    /// The parameters are checked against the capabilities of the phy before they are sent.
+    pub fn connect(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        params: &ConnectParams,
+    ) -> Result<(), Error> {
+        params.validate(phy)?;
+        let mut tx_msg = self.prepare_message(Command::Connect, MessageMode::Acknowledge)?;
+        params.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Open a socket receiving the MLME events of the nl80211 family
+    pub fn mlme_event_socket(&self) -> Result<Socket, Error> {
+        self.subscribe("mlme")
+    }
+
+    /// Connect to a BSS and wait for the result
+    ///
+    /// A rejected connection is returned as a result with a failure status, a result not reported
+    /// within `timeout` results in a `TimedOut` error.
+    pub fn connect_and_wait(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        params: &ConnectParams,
+        timeout: Option<Duration>,
+    ) -> Result<ConnectResult, Error> {
+        let mut event_socket = self.mlme_event_socket()?;
+        self.connect(socket, phy, params)?;
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            for msg in self.receive_device_events(&mut event_socket, deadline)? {
+                if let Some(ConnectionEvent::Connect(result)) = ConnectionEvent::from_message(&msg)
+                {
+                    return Ok(result);
+                }
+            }
+        }
+    }
+
+    /// Wait for connect, roam and disconnect events on a socket opened with `mlme_event_socket`
+    ///
+    /// Fails with `TimedOut` if no event for this interface arrives within `timeout`.
+    pub fn wait_connection_events(
+        &self,
+        event_socket: &mut Socket,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<ConnectionEvent>, Error> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let events: Vec<ConnectionEvent> = self
+                .receive_device_events(event_socket, deadline)?
+                .iter()
+                .filter_map(ConnectionEvent::from_message)
+                .collect();
+            if !events.is_empty() {
+                return Ok(events);
+            }
+        }
+    }
+
+    /// Authenticate with a BSS, the result is reported with an authenticate MLME event
+    ///
+    /// The parameters are checked against the capabilities of the phy before they are sent.
+    pub fn authenticate(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        params: &AuthenticateParams,
+    ) -> Result<(), Error> {
+        params.validate(phy)?;
+        let mut tx_msg = self.prepare_message(Command::Authenticate, MessageMode::Acknowledge)?;
+        params.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Associate with an authenticated BSS, the result is reported with an associate MLME event
+    ///
+    /// The parameters are checked against the capabilities of the phy before they are sent.
+    pub fn associate(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        params: &AssociateParams,
+    ) -> Result<(), Error> {
+        params.validate(phy)?;
+        let mut tx_msg = self.prepare_message(Command::Associate, MessageMode::Acknowledge)?;
+        params.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a deauthenticate or disassociate frame to a BSS
+    fn leave_bss(
+        &self,
+        socket: &mut Socket,
+        command: Command,
+        bssid: HardwareAddress,
+        reason: ReasonCode,
+        local_state_change: bool,
+    ) -> Result<(), Error> {
+        let mut tx_msg = self.prepare_message(command, MessageMode::Acknowledge)?;
+        tx_msg.append_attribute(Attribute::new(attributes::Attribute::Mac, bssid));
+        tx_msg.append_attribute(Attribute::new(
+            attributes::Attribute::ReasonCode,
+            u16::from(reason),
+        ));
+        if local_state_change {
+            tx_msg.append_attribute(flag_attribute(attributes::Attribute::LocalStateChange));
+        }
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deauthenticate from a BSS
+    ///
+    /// With `local_state_change` only the local state is updated and no frame is sent.
+    pub fn deauthenticate(
+        &self,
+        socket: &mut Socket,
+        bssid: HardwareAddress,
+        reason: ReasonCode,
+        local_state_change: bool,
+    ) -> Result<(), Error> {
+        self.leave_bss(
+            socket,
+            Command::Deauthenticate,
+            bssid,
+            reason,
+            local_state_change,
+        )
+    }
+
+    /// Disassociate from a BSS
+    ///
+    /// With `local_state_change` only the local state is updated and no frame is sent.
+    pub fn disassociate(
+        &self,
+        socket: &mut Socket,
+        bssid: HardwareAddress,
+        reason: ReasonCode,
+        local_state_change: bool,
+    ) -> Result<(), Error> {
+        self.leave_bss(
+            socket,
+            Command::Disassociate,
+            bssid,
+            reason,
+            local_state_change,
+        )
+    }
+
+    /// Start operating as an access point
+    ///
+    /// The configuration is checked against the capabilities of the phy before it is sent.
+    pub fn start_ap(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        config: &BeaconConfig,
+    ) -> Result<(), Error> {
+        config.validate(phy)?;
+        let mut tx_msg = self.prepare_message(Command::StartAp, MessageMode::Acknowledge)?;
+        config.append_attributes(&mut tx_msg, self.mac);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Update the beacon of a running access point
+    pub fn set_beacon(&self, socket: &mut Socket, config: &BeaconConfig) -> Result<(), Error> {
+        let mut tx_msg = self.prepare_message(Command::SetBeacon, MessageMode::Acknowledge)?;
+        config.append_beacon_attributes(&mut tx_msg, self.mac);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Change the BSS parameters of a running access point
+    pub fn set_bss(&self, socket: &mut Socket, params: &BssParameters) -> Result<(), Error> {
+        params.validate()?;
+        let mut tx_msg = self.prepare_message(Command::SetBss, MessageMode::Acknowledge)?;
+        params.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Switch an access point or mesh interface to another channel
+    ///
+    /// The switch happens after `count` beacons, `csa_beacon` is used until then and
+    /// `beacon_after` on the new channel. With `block_tx` stations are told not to transmit until
+    /// the switch.
+    pub fn channel_switch(
+        &self,
+        socket: &mut Socket,
+        chandef: &Chandef,
+        count: u8,
+        block_tx: bool,
+        beacon_after: &BeaconConfig,
+        csa_beacon: &BeaconConfig,
+    ) -> Result<(), Error> {
+        let mut tx_msg = self.prepare_message(Command::ChannelSwitch, MessageMode::Acknowledge)?;
+        chandef.append_attributes(&mut tx_msg);
+        tx_msg.append_attribute(Attribute::new(
+            attributes::Attribute::ChSwitchCount,
+            u32::from(count),
         ));
+        if block_tx {
+            tx_msg.append_attribute(flag_attribute(attributes::Attribute::ChSwitchBlockTx));
+        }
+        beacon_after.append_beacon_attributes(&mut tx_msg, self.mac);
+        csa_beacon.append_channel_switch_attributes(
+            &mut tx_msg,
+            self.mac,
+            chandef,
+            count,
+            block_tx,
+        );
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait for channel switch events on a socket opened with `mlme_event_socket`
+    ///
+    /// Fails with `TimedOut` if no event for this interface arrives within `timeout`.
+    pub fn wait_channel_switch_events(
+        &self,
+        event_socket: &mut Socket,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<ChannelSwitchEvent>, Error> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let events: Vec<ChannelSwitchEvent> = self
+                .receive_device_events(event_socket, deadline)?
+                .iter()
+                .filter_map(ChannelSwitchEvent::from_message)
+                .collect();
+            if !events.is_empty() {
+                return Ok(events);
+            }
+        }
+    }
+
+    /// Stop operating as an access point
+    pub fn stop_ap(&self, socket: &mut Socket) -> Result<(), Error> {
+        socket.send_message(&self.prepare_message(Command::StopAp, MessageMode::Acknowledge)?)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Join a mesh, the interface must be a mesh point
+    ///
+    /// The setup is checked against the capabilities of the phy before it is sent.
+    pub fn join_mesh(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        setup: &MeshSetup,
+    ) -> Result<(), Error> {
+        setup.validate(phy)?;
+        let mut tx_msg = self.prepare_message(Command::JoinMesh, MessageMode::Acknowledge)?;
+        setup.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Leave the mesh
+    pub fn leave_mesh(&self, socket: &mut Socket) -> Result<(), Error> {
+        socket
+            .send_message(&self.prepare_message(Command::LeaveMesh, MessageMode::Acknowledge)?)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the configuration of the mesh the interface has joined
+    pub fn get_mesh_config(&self, socket: &mut Socket) -> Result<MeshConfig, Error> {
+        socket.send_message(
+            &self.prepare_message(Command::GetMeshConfig, MessageMode::Acknowledge)?,
+        )?;
+        let mut config = None;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+            for m in messages {
+                if m.header.identifier != self.family.id {
+                    continue;
+                }
+                let (_, msg) = generic::Message::unpack(&m.data)?;
+                if msg.command == Command::GetMeshConfig {
+                    config = Some(MeshConfig::from_message(&msg)?);
+                }
+            }
+        }
+        config.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Mesh configuration not found").into()
+        })
+    }
+
+    /// Change the configuration of the mesh, parameters not set are left unchanged
+    pub fn set_mesh_config(&self, socket: &mut Socket, config: &MeshConfig) -> Result<(), Error> {
+        let mut tx_msg = self.prepare_message(Command::SetMeshConfig, MessageMode::Acknowledge)?;
+        config.append_attributes(&mut tx_msg);
         socket.send_message(&tx_msg)?;
         loop {
             let messages = socket.receive_messages()?;
@@ -310,6 +1340,181 @@ impl WirelessInterface {
         Ok(())
     }
 
+    /// Get the mesh paths of the HWMP forwarding table
+    pub fn get_mpath(&self, socket: &mut Socket) -> Result<Vec<MeshPath>, Error> {
+        let msg = self.prepare_message(Command::GetMeshPath, MessageMode::Dump)?;
+        let mut paths = vec![];
+        for message in self.dump_messages(socket, &msg)? {
+            if message.command == Command::NewMeshPath {
+                paths.push(MeshPath::from_message(&message)?);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Get the mesh proxy paths, the destinations outside the mesh and their proxies
+    pub fn get_mpp(&self, socket: &mut Socket) -> Result<Vec<MeshPath>, Error> {
+        let msg = self.prepare_message(Command::GetMeshProxyPath, MessageMode::Dump)?;
+        let mut paths = vec![];
+        for message in self.dump_messages(socket, &msg)? {
+            if message.command == Command::NewMeshPath {
+                paths.push(MeshPath::from_message(&message)?);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Send a mesh path command for the destination and wait for the acknowledgement
+    fn mesh_path_command(
+        &self,
+        socket: &mut Socket,
+        command: Command,
+        destination: Option<HardwareAddress>,
+        next_hop: Option<HardwareAddress>,
+    ) -> Result<(), Error> {
+        let mut tx_msg = self.prepare_message(command, MessageMode::Acknowledge)?;
+        if let Some(destination) = destination {
+            tx_msg.append_attribute(Attribute::new(attributes::Attribute::Mac, destination));
+        }
+        if let Some(next_hop) = next_hop {
+            tx_msg.append_attribute(Attribute::new(
+                attributes::Attribute::MpathNextHop,
+                next_hop,
+            ));
+        }
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a fixed mesh path to the destination through the next hop
+    pub fn new_mpath(
+        &self,
+        socket: &mut Socket,
+        destination: HardwareAddress,
+        next_hop: HardwareAddress,
+    ) -> Result<(), Error> {
+        self.mesh_path_command(
+            socket,
+            Command::NewMeshPath,
+            Some(destination),
+            Some(next_hop),
+        )
+    }
+
+    /// Change the next hop of the mesh path to the destination
+    pub fn set_mpath(
+        &self,
+        socket: &mut Socket,
+        destination: HardwareAddress,
+        next_hop: HardwareAddress,
+    ) -> Result<(), Error> {
+        self.mesh_path_command(
+            socket,
+            Command::SetMeshPath,
+            Some(destination),
+            Some(next_hop),
+        )
+    }
+
+    /// Delete the mesh path to the destination, or all mesh paths if `None`
+    pub fn del_mpath(
+        &self,
+        socket: &mut Socket,
+        destination: Option<HardwareAddress>,
+    ) -> Result<(), Error> {
+        self.mesh_path_command(socket, Command::DelMeshPath, destination, None)
+    }
+
+    /// Join or create an IBSS, the interface must be an ad-hoc interface
+    ///
+    /// The parameters are checked against the capabilities of the phy before they are sent.
+    pub fn join_ibss(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        params: &IbssParams,
+    ) -> Result<(), Error> {
+        params.validate(phy)?;
+        let mut tx_msg = self.prepare_message(Command::JoinIbss, MessageMode::Acknowledge)?;
+        params.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Leave the IBSS
+    pub fn leave_ibss(&self, socket: &mut Socket) -> Result<(), Error> {
+        socket
+            .send_message(&self.prepare_message(Command::LeaveIbss, MessageMode::Acknowledge)?)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start communicating outside the context of a BSS (OCB) on the channel
+    ///
+    /// The interface must be an OCB interface.
+    pub fn join_ocb(&self, socket: &mut Socket, chandef: &Chandef) -> Result<(), Error> {
+        let mut tx_msg = self.prepare_message(Command::JoinOcb, MessageMode::Acknowledge)?;
+        chandef.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop communicating outside the context of a BSS
+    pub fn leave_ocb(&self, socket: &mut Socket) -> Result<(), Error> {
+        socket.send_message(&self.prepare_message(Command::LeaveOcb, MessageMode::Acknowledge)?)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait for MLME events on a socket opened with `mlme_event_socket`
+    ///
+    /// Fails with `TimedOut` if no event for this interface arrives within `timeout`.
+    pub fn wait_mlme_events(
+        &self,
+        event_socket: &mut Socket,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<MlmeEvent>, Error> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let events: Vec<MlmeEvent> = self
+                .receive_device_events(event_socket, deadline)?
+                .iter()
+                .filter_map(MlmeEvent::from_message)
+                .collect();
+            if !events.is_empty() {
+                return Ok(events);
+            }
+        }
+    }
+
     pub fn get_regulatory(&self, socket: &mut Socket) -> Result<(), Error> {
         let msg = generic::Message::new(self.family.id, Command::GetRegulatory, MessageMode::Dump);
         /* let msg = self.prepare_message(Command::GetRegulatory,
@@ -356,11 +1561,11 @@ impl WirelessInterface {
         Ok(())
     }
 
-    pub fn set_channel(&self, socket: &mut Socket, frequency: u32) -> Result<(), Error> {
+    /// Set the operating channel of the interface
+    pub fn set_channel(&self, socket: &mut Socket, chandef: &Chandef) -> Result<(), Error> {
         {
             let mut msg = self.prepare_message(Command::SetChannel, MessageMode::Acknowledge)?;
-            let attr = Attribute::new(attributes::Attribute::WiphyFreq, frequency);
-            msg.append_attribute(attr);
+            chandef.append_attributes(&mut msg);
             socket.send_message(&msg)?;
         }
         loop {
@@ -377,118 +1582,126 @@ impl WirelessInterface {
         Ok(())
     }
 
-    pub fn get_station(&self, socket: &mut Socket) -> Result<(), Error> {
-        {
-            let msg = self.prepare_message(Command::GetStation, MessageMode::Dump)?;
-            socket.send_message(&msg)?;
+    /// Get information about the stations known to the interface
+    pub fn get_station(&self, socket: &mut Socket) -> Result<Vec<StationInfo>, Error> {
+        let msg = self.prepare_message(Command::GetStation, MessageMode::Dump)?;
+        let mut stations = vec![];
+        for message in self.dump_messages(socket, &msg)? {
+            if message.command == Command::NewStation {
+                stations.push(StationInfo::from_message(&message)?);
+            }
         }
+        Ok(stations)
+    }
+
+    /// Get information about the station with the given MAC address
+    ///
+    /// Returns `None` if the station is not known to the interface.
+    pub fn get_station_by_mac(
+        &self,
+        socket: &mut Socket,
+        mac: HardwareAddress,
+    ) -> Result<Option<StationInfo>, Error> {
+        let mut msg = self.prepare_message(Command::GetStation, MessageMode::None)?;
+        msg.append_attribute(Attribute::new(attributes::Attribute::Mac, mac));
+        socket.send_message(&msg)?;
         loop {
-            let messages = socket.receive_messages()?;
+            let messages = match socket.receive_messages() {
+                Ok(messages) => messages,
+                Err(Error::Io(ref error)) if error.raw_os_error() == Some(libc::ENOENT) => {
+                    return Ok(None);
+                }
+                Err(error) => return Err(error),
+            };
             if messages.is_empty() {
                 break;
             }
-
             for m in messages {
+                if m.header.identifier != self.family.id {
+                    continue;
+                }
                 let (_, msg) = generic::Message::unpack(&m.data)?;
-                match Command::from(msg.command) {
-                    Command::NewStation => {
-                        for ref attr in &msg.attributes {
-                            let attr_id = attributes::Attribute::from(attr.identifier);
-                            match attr_id {
-                                attributes::Attribute::Ifindex
-                                | attributes::Attribute::Generation
-                                | attributes::Attribute::Mac => {}
-                                attributes::Attribute::StaInfo => {
-                                    println!("Station");
-                                    let (_, attrs) = Attribute::unpack_all(&attr.as_bytes());
-                                    for attr in attrs {
-                                        let attr_id =
-                                            attributes::StationInformationAttributes::from(
-                                                attr.identifier,
-                                            );
-                                        match attr_id {
-                                            attributes::StationInformationAttributes::InactiveTime => {
-                                                println!("Inactive time: {} ms", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::ReceivedBytes => {
-                                                println!("Received bytes: {}", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::TransmittedBytes => {
-                                                println!("Transmitted bytes: {}", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::Signal => {
-                                                println!("Signal: {} dBm", attr.as_i8().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::ReceivedPackets => {
-                                                println!("Received Packets: {}", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::TransmittedPackets => {
-                                                println!("Transmitted Packets: {}", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::TransmitRetries => {
-                                                println!("Transmit Retries: {}", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::TransmitFailures => {
-                                                println!("Transmit Failures: {}", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::SignalAverage => {
-                                                println!("Signal Average: {} dBm", attr.as_i8().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::ConnectedTime => {
-                                                println!("Connected time: {} ms", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::BeaconLoss => {
-                                                println!("Beacon Loss: {}", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::TimingOffset => {
-                                                println!("Timing Offset: {} ms", attr.as_i64().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::ReceivedBytes64 => {
-                                                println!("Received bytes: {}", attr.as_u64().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::TransmittedBytes64 => {
-                                                println!("Transmitted bytes: {}", attr.as_u64().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::ChainSignal => {
-                                                println!("Chain Signal: {} dBm", attr.as_i8().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::ChainSignalAverage => {
-                                                println!("Chain Signal Average: {} dBm", attr.as_i8().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::ExpectedThroughput => {
-                                                println!("Expected Throughput: {} kbps", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::ReceiveDropped => {
-                                                println!("Dropped receive packets: {}", attr.as_u32().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::ReceivedBeacons => {
-                                                println!("Received Beacons: {}", attr.as_u64().unwrap());
-                                            }
-                                            attributes::StationInformationAttributes::BeaconsSignalAverage => {
-                                                println!("Beacon Signal Average: {}", attr.as_i8().unwrap());
-                                            }
-                                            _ => {
-                                                println!("** Attribute: {:?}, Len: {}", attr_id, attr.len());
-                                            }
-                                        }
-                                    }
-                                }
-                                _ => {
-                                    println!("Attribute: {:?}, Len: {}", attr_id, attr.len());
-                                }
-                            }
-                        }
-                    }
-                    cmd => {
-                        println!("Command {:?}", cmd);
-                    }
+                if msg.command == Command::NewStation {
+                    return Ok(Some(StationInfo::from_message(&msg)?));
                 }
             }
         }
+        Ok(None)
+    }
+
+    /// Add a station to an access point
+    ///
+    /// The parameters are checked against the capabilities of the phy before they are sent.
+    pub fn new_station(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        params: &StationParams,
+    ) -> Result<(), Error> {
+        params.validate_new(phy)?;
+        let mut tx_msg = self.prepare_message(Command::NewStation, MessageMode::Acknowledge)?;
+        params.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Change the parameters of a station
+    ///
+    /// The parameters are checked against the capabilities of the phy before they are sent.
+    pub fn set_station(
+        &self,
+        socket: &mut Socket,
+        phy: &WirelessPhy,
+        params: &StationParams,
+    ) -> Result<(), Error> {
+        params.validate(phy)?;
+        let mut tx_msg = self.prepare_message(Command::SetStation, MessageMode::Acknowledge)?;
+        params.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove a station, or all stations if `mac` is `None`
+    ///
+    /// With `frame` the station is sent a deauthentication or disassociation frame first.
+    pub fn del_station(
+        &self,
+        socket: &mut Socket,
+        mac: Option<HardwareAddress>,
+        frame: Option<DisconnectFrame>,
+    ) -> Result<(), Error> {
+        let mut tx_msg = self.prepare_message(Command::DelStation, MessageMode::Acknowledge)?;
+        if let Some(mac) = mac {
+            tx_msg.append_attribute(Attribute::new(attributes::Attribute::Mac, mac));
+        }
+        if let Some(ref frame) = frame {
+            frame.append_attributes(&mut tx_msg);
+        }
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
         Ok(())
     }
 }
 
+/// Get the wireless interfaces, including wireless devices without a network interface such as
+/// P2P devices
 pub fn get_wireless_interfaces(
     socket: &mut Socket,
     family: &generic::Family,
@@ -1,7 +1,11 @@
 use super::join_to_string;
-use crate::attributes::{self, Attribute, InterfaceType};
+use crate::attributes::{
+    self, AccessCategory, Attribute, InterfaceType, TxPowerSetting, TxqAttribute,
+};
 use crate::commands::Command;
 use crate::information_element::CipherSuite;
+use crate::pack::{flag_attribute, nested_attribute, nested_attribute_array};
+use crate::wireless_interface::{InterfaceOptions, WirelessInterface};
 use netlink_rust as netlink;
 use netlink_rust::generic;
 use netlink_rust::{ConvertFrom, Error, NativeUnpack};
@@ -23,6 +27,90 @@ pub struct WirelessPhy {
     commands: Vec<Command>,
     if_types: InterfaceTypeFlags,
     software_if_types: InterfaceTypeFlags,
+    features: FeatureFlags,
+    extended_features: ExtendedFeaturesFlags,
+    max_num_scan_ssids: u8,
+    max_scan_ie_len: u16,
+    max_num_sched_scan_ssids: u8,
+    max_sched_scan_ie_len: u16,
+    max_match_sets: u8,
+    max_num_sched_scan_plans: u32,
+    max_scan_plan_interval: u32,
+    max_scan_plan_iterations: u32,
+    sched_scan_max_reqs: u32,
+    antenna_avail_tx: u32,
+    antenna_avail_rx: u32,
+}
+
+/// Lowest fragmentation threshold accepted by the kernel
+const MIN_FRAG_THRESHOLD: u32 = 256;
+/// Threshold value disabling RTS/CTS or fragmentation
+const THRESHOLD_DISABLED: u32 = 0xffff_ffff;
+/// Antenna bitmap selecting all available antennas
+const ALL_ANTENNAS: u32 = 0xffff_ffff;
+const MAX_CONTENTION_WINDOW: u16 = 32767;
+const MAX_AIFS: u8 = 15;
+
+/// Transmit power setting of a phy
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TxPower {
+    /// Let the driver select the transmit power
+    Automatic,
+    /// Limit the transmit power to the level in mBm
+    Limited(i32),
+    /// Use a fixed transmit power level in mBm
+    Fixed(i32),
+}
+
+/// Transmit queue parameters of an access category
+#[derive(Clone, Debug, PartialEq)]
+pub struct WiphyTxqParams {
+    pub access_category: AccessCategory,
+    /// Maximum burst duration in units of 32 us, zero for a single frame per access
+    pub txop: u16,
+    /// Minimum contention window, a power of two minus one
+    pub cw_min: u16,
+    /// Maximum contention window, a power of two minus one
+    pub cw_max: u16,
+    /// Arbitration inter-frame space number
+    pub aifs: u8,
+}
+
+impl WiphyTxqParams {
+    /// Check the contention windows and the AIFS number
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        let valid_window = |window: u16| window & window.wrapping_add(1) == 0;
+        if self.cw_min > MAX_CONTENTION_WINDOW
+            || self.cw_max > MAX_CONTENTION_WINDOW
+            || !valid_window(self.cw_min)
+            || !valid_window(self.cw_max)
+            || self.cw_min > self.cw_max
+        {
+            return invalid("Invalid contention window");
+        }
+        if self.aifs == 0 || self.aifs > MAX_AIFS {
+            return invalid("Invalid AIFS number");
+        }
+        Ok(())
+    }
+
+    /// Parameters of one access category as an item of the nested parameter array
+    fn to_attribute(&self) -> netlink::Attribute {
+        nested_attribute(
+            0u16,
+            &[
+                netlink::Attribute::new(
+                    TxqAttribute::AccessCategory,
+                    u8::from(self.access_category.clone()),
+                ),
+                netlink::Attribute::new(TxqAttribute::Txop, self.txop),
+                netlink::Attribute::new(TxqAttribute::CwMin, self.cw_min),
+                netlink::Attribute::new(TxqAttribute::CwMax, self.cw_max),
+                netlink::Attribute::new(TxqAttribute::Aifs, self.aifs),
+            ],
+        )
+    }
 }
 
 bitflags! {
@@ -137,6 +225,16 @@ bitflags! {
         const SCAN_MIN_PREQ_CONTENT              = 1 << 30;
         const CAN_REPLACE_PTK0                   = 1 << 31;
         const ENABLE_FTM_RESPONDER               = 1 << 32;
+        const AIRTIME_FAIRNESS                   = 1 << 33;
+        const AP_PMKSA_CACHING                   = 1 << 34;
+        const SCHED_SCAN_BAND_SPECIFIC_RSSI_THOLD = 1 << 35;
+        const EXT_KEY_ID                         = 1 << 36;
+        const STA_TX_PWR                         = 1 << 37;
+        const SAE_OFFLOAD                        = 1 << 38;
+        const VLAN_OFFLOAD                       = 1 << 39;
+        const AQL                                = 1 << 40;
+        const BEACON_PROTECTION                  = 1 << 41;
+        const CONTROL_PORT_NO_PREAUTH            = 1 << 42;
     }
 }
 
@@ -147,6 +245,19 @@ impl WirelessPhy {
         let mut phy_name = String::new();
         let mut if_types = InterfaceTypeFlags::empty();
         let mut software_if_types = InterfaceTypeFlags::empty();
+        let mut features = FeatureFlags::empty();
+        let mut extended_features = ExtendedFeaturesFlags::empty();
+        let mut max_num_scan_ssids = 0;
+        let mut max_scan_ie_len = 0;
+        let mut max_num_sched_scan_ssids = 0;
+        let mut max_sched_scan_ie_len = 0;
+        let mut max_match_sets = 0;
+        let mut max_num_sched_scan_plans = 0;
+        let mut max_scan_plan_interval = 0;
+        let mut max_scan_plan_iterations = 0;
+        let mut sched_scan_max_reqs = 0;
+        let mut antenna_avail_tx = 0;
+        let mut antenna_avail_rx = 0;
         for attr in attributes {
             let identifier = Attribute::convert_from(attr.identifier);
             if let Some(identifier) = identifier {
@@ -177,10 +288,40 @@ impl WirelessPhy {
                             );
                         }
                     }
-                    Attribute::MaxNumScanSsids
-                    | Attribute::MaxNumSchedScanSsids
-                    | Attribute::MaxMatchSets
-                    | Attribute::WiphyRetryShort
+                    Attribute::MaxNumScanSsids => {
+                        max_num_scan_ssids = attr.as_u8()?;
+                    }
+                    Attribute::MaxScanIeLen => {
+                        max_scan_ie_len = attr.as_u16()?;
+                    }
+                    Attribute::MaxNumSchedScanSsids => {
+                        max_num_sched_scan_ssids = attr.as_u8()?;
+                    }
+                    Attribute::MaxSchedScanIeLen => {
+                        max_sched_scan_ie_len = attr.as_u16()?;
+                    }
+                    Attribute::MaxMatchSets => {
+                        max_match_sets = attr.as_u8()?;
+                    }
+                    Attribute::MaxNumSchedScanPlans => {
+                        max_num_sched_scan_plans = attr.as_u32()?;
+                    }
+                    Attribute::MaxScanPlanInterval => {
+                        max_scan_plan_interval = attr.as_u32()?;
+                    }
+                    Attribute::MaxScanPlanIterations => {
+                        max_scan_plan_iterations = attr.as_u32()?;
+                    }
+                    Attribute::SchedScanMaxReqs => {
+                        sched_scan_max_reqs = attr.as_u32()?;
+                    }
+                    Attribute::WiphyAntennaAvailTx => {
+                        antenna_avail_tx = attr.as_u32()?;
+                    }
+                    Attribute::WiphyAntennaAvailRx => {
+                        antenna_avail_rx = attr.as_u32()?;
+                    }
+                    Attribute::WiphyRetryShort
                     | Attribute::WiphyRetryLong
                     | Attribute::MaxNumPmkids
                     | Attribute::WiphyCoverageClass
@@ -194,10 +335,7 @@ impl WirelessPhy {
                             );
                         }
                     }
-                    Attribute::MaxScanIeLen
-                    | Attribute::MaxSchedScanIeLen
-                    | Attribute::MacAclMax
-                    | Attribute::MaxRemainOnChannelDuration => {
+                    Attribute::MacAclMax | Attribute::MaxRemainOnChannelDuration => {
                         if attr.as_u16().is_err() {
                             println!(
                                 "[{:?}] {:?} {} Invalid type",
@@ -208,18 +346,12 @@ impl WirelessPhy {
                         }
                     }
                     Attribute::Bands
-                    | Attribute::MaxNumSchedScanPlans
-                    | Attribute::MaxScanPlanInterval
-                    | Attribute::MaxScanPlanIterations
                     | Attribute::WiphyFragThreshold
                     | Attribute::WiphyRtsThreshold
-                    | Attribute::WiphyAntennaAvailTx
-                    | Attribute::WiphyAntennaAvailRx
                     | Attribute::DeviceApSme
                     | Attribute::TransmitQueueLimit
                     | Attribute::TransmitQueueMemoryLimit
-                    | Attribute::TransmitQueueSchedulerBytes
-                    | Attribute::SchedScanMaxReqs => {
+                    | Attribute::TransmitQueueSchedulerBytes => {
                         if attr.as_u32().is_err() {
                             println!(
                                 "[{:?}] {:?} {} Invalid type",
@@ -230,14 +362,12 @@ impl WirelessPhy {
                         }
                     }
                     Attribute::ExtFeatures => {
+                        // Feature n is bit n % 8 of octet n / 8
                         let mut flags = 0u64;
-                        if attr.len() >= 1 {
-                            for b in attr.as_bytes() {
-                                flags <<= 8;
-                                flags |= u64::from(b);
-                            }
+                        for (n, b) in attr.as_bytes().into_iter().take(8).enumerate() {
+                            flags |= u64::from(b) << (n * 8);
                         }
-                        let extended_features = ExtendedFeaturesFlags::from_bits_truncate(flags);
+                        extended_features = ExtendedFeaturesFlags::from_bits_truncate(flags);
                         println!(
                             "[{:?}] {:?} LEN: {} {:#x} {:?}",
                             phy_id,
@@ -266,13 +396,13 @@ impl WirelessPhy {
                         if_types = flags;
                     }
                     Attribute::FeatureFlags => {
-                        let ff = FeatureFlags::from_bits_truncate(attr.as_u32()?);
+                        features = FeatureFlags::from_bits_truncate(attr.as_u32()?);
                         println!(
                             "[{:?}] {:?} LEN: {} {:?}",
                             phy_id,
                             identifier,
                             attr.len(),
-                            ff
+                            features
                         );
                     }
                     Attribute::CipherSuites => {
@@ -412,6 +542,19 @@ impl WirelessPhy {
                 commands,
                 if_types,
                 software_if_types,
+                features,
+                extended_features,
+                max_num_scan_ssids,
+                max_scan_ie_len,
+                max_num_sched_scan_ssids,
+                max_sched_scan_ie_len,
+                max_match_sets,
+                max_num_sched_scan_plans,
+                max_scan_plan_interval,
+                max_scan_plan_iterations,
+                sched_scan_max_reqs,
+                antenna_avail_tx,
+                antenna_avail_rx,
             })
         } else {
             Err(io::Error::new(io::ErrorKind::NotFound, "Wireless Phy Not Found").into())
@@ -419,6 +562,442 @@ impl WirelessPhy {
     }
 }
 
+impl WirelessPhy {
+    /// Phy identifier, the same as `WirelessInterface::phy_id` of its interfaces
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Supported interface types
+    pub fn interface_types(&self) -> InterfaceTypeFlags {
+        self.if_types
+    }
+
+    /// Interface types which can be added regardless of the interface combinations
+    pub fn software_interface_types(&self) -> InterfaceTypeFlags {
+        self.software_if_types
+    }
+
+    /// The phy supports interfaces of the type
+    pub fn supports_interface_type(&self, interface_type: InterfaceType) -> bool {
+        self.if_types
+            .contains(InterfaceTypeFlags::from(interface_type))
+    }
+
+    pub fn features(&self) -> FeatureFlags {
+        self.features
+    }
+
+    pub fn extended_features(&self) -> ExtendedFeaturesFlags {
+        self.extended_features
+    }
+
+    /// Maximum number of SSIDs in a scan request
+    pub fn max_num_scan_ssids(&self) -> u8 {
+        self.max_num_scan_ssids
+    }
+
+    /// Maximum length of the information elements added to a scan request
+    pub fn max_scan_ie_len(&self) -> u16 {
+        self.max_scan_ie_len
+    }
+
+    /// Maximum number of SSIDs in a scheduled scan request
+    pub fn max_num_sched_scan_ssids(&self) -> u8 {
+        self.max_num_sched_scan_ssids
+    }
+
+    /// Maximum length of the information elements added to a scheduled scan request
+    pub fn max_sched_scan_ie_len(&self) -> u16 {
+        self.max_sched_scan_ie_len
+    }
+
+    /// Maximum number of match sets in a scheduled scan request
+    pub fn max_match_sets(&self) -> u8 {
+        self.max_match_sets
+    }
+
+    /// Maximum number of scan plans in a scheduled scan request
+    pub fn max_num_sched_scan_plans(&self) -> u32 {
+        self.max_num_sched_scan_plans
+    }
+
+    /// Maximum interval in seconds of a scan plan
+    pub fn max_scan_plan_interval(&self) -> u32 {
+        self.max_scan_plan_interval
+    }
+
+    /// Maximum number of iterations of a scan plan
+    pub fn max_scan_plan_iterations(&self) -> u32 {
+        self.max_scan_plan_iterations
+    }
+
+    /// Maximum number of concurrent scheduled scans, zero if only a single legacy scheduled
+    /// scan is supported
+    pub fn sched_scan_max_reqs(&self) -> u32 {
+        self.sched_scan_max_reqs
+    }
+
+    /// Bitmap of the antennas which can be used for transmitting, zero if the antennas can not
+    /// be configured
+    pub fn antenna_avail_tx(&self) -> u32 {
+        self.antenna_avail_tx
+    }
+
+    /// Bitmap of the antennas which can be used for receiving, zero if the antennas can not be
+    /// configured
+    pub fn antenna_avail_rx(&self) -> u32 {
+        self.antenna_avail_rx
+    }
+}
+
+impl WirelessPhy {
+    /// Create a virtual interface on the phy
+    ///
+    /// The name, type and options are checked against the capabilities of the phy first.
+    pub fn new_interface(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        name: &str,
+        interface_type: InterfaceType,
+        options: &InterfaceOptions,
+    ) -> Result<WirelessInterface, Error> {
+        options.validate(self, name, &interface_type)?;
+        let mut tx_msg = generic::Message::new(
+            family.id,
+            Command::NewInterface,
+            netlink::MessageMode::Acknowledge,
+        );
+        tx_msg.append_attribute(netlink::Attribute::new(Attribute::Wiphy, self.identifier));
+        tx_msg.append_attribute(netlink::Attribute::new_string_with_nul(
+            Attribute::Ifname,
+            name,
+        ));
+        tx_msg.append_attribute(netlink::Attribute::new(
+            Attribute::Iftype,
+            u32::from(interface_type),
+        ));
+        options.append_attributes(&mut tx_msg);
+        socket.send_message(&tx_msg)?;
+        let mut interface = None;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+            for m in messages {
+                if m.header.identifier != family.id {
+                    continue;
+                }
+                let (_, msg) = generic::Message::unpack(&m.data)?;
+                if msg.command == Command::NewInterface {
+                    interface = Some(WirelessInterface::from_message(msg, family.clone())?);
+                }
+            }
+        }
+        interface.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Wireless Interface Not Found").into()
+        })
+    }
+}
+
+impl WirelessPhy {
+    /// Send a set wiphy message with the attributes and wait for the acknowledgement
+    fn set_wiphy(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        attributes: Vec<netlink::Attribute>,
+    ) -> Result<(), Error> {
+        let mut tx_msg = generic::Message::new(
+            family.id,
+            Command::SetWiphy,
+            netlink::MessageMode::Acknowledge,
+        );
+        tx_msg.append_attribute(netlink::Attribute::new(Attribute::Wiphy, self.identifier));
+        for attribute in attributes {
+            tx_msg.append_attribute(attribute);
+        }
+        socket.send_message(&tx_msg)?;
+        loop {
+            let messages = socket.receive_messages()?;
+            if messages.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rename the phy
+    pub fn set_name(
+        &mut self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        name: &str,
+    ) -> Result<(), Error> {
+        if name.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid phy name").into());
+        }
+        self.set_wiphy(
+            socket,
+            family,
+            vec![netlink::Attribute::new_string_with_nul(
+                Attribute::WiphyName,
+                name,
+            )],
+        )?;
+        self.name = name.to_string();
+        Ok(())
+    }
+
+    /// Set the transmit power of the phy
+    pub fn set_tx_power(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        power: TxPower,
+    ) -> Result<(), Error> {
+        let (setting, level) = match power {
+            TxPower::Automatic => (TxPowerSetting::Automatic, None),
+            TxPower::Limited(level) => (TxPowerSetting::Limited, Some(level)),
+            TxPower::Fixed(level) => (TxPowerSetting::Fixed, Some(level)),
+        };
+        let mut attributes = vec![netlink::Attribute::new(
+            Attribute::WiphyTxPowerSetting,
+            u32::from(setting),
+        )];
+        if let Some(level) = level {
+            attributes.push(netlink::Attribute::new(
+                Attribute::WiphyTxPowerLevel,
+                level as u32,
+            ));
+        }
+        self.set_wiphy(socket, family, attributes)
+    }
+
+    /// Set the RTS threshold in octets, `None` disables RTS/CTS
+    pub fn set_rts_threshold(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        threshold: Option<u32>,
+    ) -> Result<(), Error> {
+        let threshold = threshold.unwrap_or(THRESHOLD_DISABLED);
+        self.set_wiphy(
+            socket,
+            family,
+            vec![netlink::Attribute::new(
+                Attribute::WiphyRtsThreshold,
+                threshold,
+            )],
+        )
+    }
+
+    /// Set the fragmentation threshold in octets, `None` disables fragmentation
+    pub fn set_frag_threshold(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        threshold: Option<u32>,
+    ) -> Result<(), Error> {
+        if matches!(threshold, Some(threshold) if threshold < MIN_FRAG_THRESHOLD) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid fragmentation threshold",
+            )
+            .into());
+        }
+        let threshold = threshold.unwrap_or(THRESHOLD_DISABLED);
+        self.set_wiphy(
+            socket,
+            family,
+            vec![netlink::Attribute::new(
+                Attribute::WiphyFragThreshold,
+                threshold,
+            )],
+        )
+    }
+
+    /// Set the retry limits for frames shorter and longer than the RTS threshold
+    pub fn set_retry_limits(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        short: u8,
+        long: u8,
+    ) -> Result<(), Error> {
+        if short == 0 || long == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid retry limit").into());
+        }
+        self.set_wiphy(
+            socket,
+            family,
+            vec![
+                netlink::Attribute::new(Attribute::WiphyRetryShort, short),
+                netlink::Attribute::new(Attribute::WiphyRetryLong, long),
+            ],
+        )
+    }
+
+    /// Set the coverage class, extending the ACK timeout by 3 us per class
+    pub fn set_coverage_class(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        coverage_class: u8,
+    ) -> Result<(), Error> {
+        self.set_wiphy(
+            socket,
+            family,
+            vec![netlink::Attribute::new(
+                Attribute::WiphyCoverageClass,
+                coverage_class,
+            )],
+        )
+    }
+
+    /// Let the driver estimate the ACK timeout dynamically instead of using a coverage class
+    pub fn set_dynack(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+    ) -> Result<(), Error> {
+        if !self.features.contains(FeatureFlags::ACKTO_ESTIMATION) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ACK timeout estimation not supported",
+            )
+            .into());
+        }
+        self.set_wiphy(socket, family, vec![flag_attribute(Attribute::WiphyDynAck)])
+    }
+
+    /// Select the antennas used for transmitting and receiving as bitmaps
+    ///
+    /// The antennas must be a non-empty subset of the available antennas, `u32::MAX` selects all
+    /// available antennas.
+    pub fn set_antennas(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        tx: u32,
+        rx: u32,
+    ) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if self.antenna_avail_tx == 0 && self.antenna_avail_rx == 0 {
+            return invalid("Antenna configuration not supported");
+        }
+        if tx == 0 || (tx != ALL_ANTENNAS && tx & !self.antenna_avail_tx != 0) {
+            return invalid("Transmit antennas not available");
+        }
+        if rx == 0 || (rx != ALL_ANTENNAS && rx & !self.antenna_avail_rx != 0) {
+            return invalid("Receive antennas not available");
+        }
+        self.set_wiphy(
+            socket,
+            family,
+            vec![
+                netlink::Attribute::new(Attribute::WiphyAntennaTx, tx),
+                netlink::Attribute::new(Attribute::WiphyAntennaRx, rx),
+            ],
+        )
+    }
+
+    /// Send a transmit queue setting, the phy must support transmit queues
+    fn set_txq(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        attribute: Attribute,
+        value: u32,
+    ) -> Result<(), Error> {
+        if !self.extended_features.contains(ExtendedFeaturesFlags::TXQS) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "TXQs not supported").into());
+        }
+        self.set_wiphy(
+            socket,
+            family,
+            vec![netlink::Attribute::new(attribute, value)],
+        )
+    }
+
+    /// Set the maximum number of frames in the transmit queues
+    pub fn set_txq_limit(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        limit: u32,
+    ) -> Result<(), Error> {
+        self.set_txq(socket, family, Attribute::TransmitQueueLimit, limit)
+    }
+
+    /// Set the maximum memory in bytes used by the transmit queues
+    pub fn set_txq_memory_limit(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        limit: u32,
+    ) -> Result<(), Error> {
+        self.set_txq(socket, family, Attribute::TransmitQueueMemoryLimit, limit)
+    }
+
+    /// Set the quantum in bytes of the transmit queue scheduler
+    pub fn set_txq_quantum(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        quantum: u32,
+    ) -> Result<(), Error> {
+        self.set_txq(
+            socket,
+            family,
+            Attribute::TransmitQueueSchedulerBytes,
+            quantum,
+        )
+    }
+
+    /// Set the transmit queue parameters of access categories
+    ///
+    /// The parameters apply to the BSS of `interface`, an access point or P2P GO interface of
+    /// the phy.
+    pub fn set_txq_params(
+        &self,
+        socket: &mut netlink::Socket,
+        family: &generic::Family,
+        interface: &WirelessInterface,
+        params: &[WiphyTxqParams],
+    ) -> Result<(), Error> {
+        let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        if interface.phy_id != self.identifier {
+            return invalid("Interface of another phy");
+        }
+        let interface_index = match (interface.interface_index, &interface.interface_type) {
+            (Some(index), InterfaceType::Ap) | (Some(index), InterfaceType::P2pGo) => index,
+            _ => return invalid("Not an access point interface"),
+        };
+        if params.is_empty() {
+            return invalid("No transmit queue parameters");
+        }
+        for param in params {
+            param.validate()?;
+        }
+        let items = params.iter().map(WiphyTxqParams::to_attribute).collect();
+        self.set_wiphy(
+            socket,
+            family,
+            vec![
+                netlink::Attribute::new(Attribute::Ifindex, interface_index),
+                nested_attribute_array(Attribute::WiphyTxqParams, items),
+            ],
+        )
+    }
+}
+
 impl PartialEq for WirelessPhy {
     fn eq(&self, other: &WirelessPhy) -> bool {
         self.identifier == other.identifier
@@ -497,3 +1076,35 @@ pub fn get_wireless_phys(
     }
     Ok(phys)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_txq_params() {
+        let params = WiphyTxqParams {
+            access_category: AccessCategory::BestEffort,
+            txop: 0,
+            cw_min: 15,
+            cw_max: 1023,
+            aifs: 3,
+        };
+        assert!(params.validate().is_ok());
+        let invalid = WiphyTxqParams {
+            cw_min: 16,
+            ..params.clone()
+        };
+        assert!(invalid.validate().is_err());
+        let invalid = WiphyTxqParams {
+            cw_min: 2047,
+            ..params.clone()
+        };
+        assert!(invalid.validate().is_err());
+        let invalid = WiphyTxqParams {
+            aifs: 0,
+            ..params.clone()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}